@@ -22,11 +22,11 @@ fn App() -> Element {
 
     let mut easing_style = use_signal(|| Easing::BackOut);
 
-    let mut mark_target = move |evt: PointerEvent| {
+    let mark_target = move |evt: PointerEvent| {
         let coords = evt.data.client_coordinates();
         let ball_size = *ball_size.peek();
         let mut to = Rect::new(coords.cast_unit(), Size2D::new(ball_size, ball_size));
-        target_position.set(Some(to.clone()));
+        target_position.set(Some(to));
         to.origin.x -= ball_size / 2.;
         to.origin.y -= ball_size / 2.;
         let anim_builder = AnimationBuilder::default()
@@ -46,7 +46,7 @@ fn App() -> Element {
     rsx! {
         div {
             style: "width: 100%; height: 100vh;",
-            onpointerdown: move |evt| mark_target(evt),
+            onpointerdown: mark_target,
             Animatable {
                 controller: animation_controller,
                 div {
@@ -0,0 +1,88 @@
+use std::{cell::Cell, rc::Rc};
+
+use web_time::Duration;
+
+/// A monotonic time source. `now` is measured from an arbitrary origin chosen by the clock
+/// and must never go backwards.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+pub type SharedClock = Rc<dyn Clock>;
+
+/// Default clock backed by `performance.now()` on the web and `Instant` elsewhere.
+/// Unaffected by wall-clock adjustments.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MonotonicClock {
+    origin: web_time::Instant,
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self {
+            origin: web_time::Instant::now(),
+        }
+    }
+}
+
+impl MonotonicClock {
+    pub fn shared() -> SharedClock {
+        Rc::new(Self::default())
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Manually advanced clock for deterministic timing. Clones share the same time,
+/// so a test can keep one copy and hand another to a stopwatch or controller.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct VirtualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn shared(&self) -> SharedClock {
+        Rc::new(self.clone())
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+
+    /// moving the clock backwards is ignored to keep it monotonic
+    pub fn set(&self, now: Duration) {
+        self.now.set(self.now.get().max(now));
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_clocks_share_time_and_never_go_back() {
+        let clock = VirtualClock::new();
+        let shared = clock.shared();
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(shared.now(), Duration::from_millis(16));
+
+        clock.set(Duration::from_millis(10));
+        assert_eq!(shared.now(), Duration::from_millis(16));
+        clock.set(Duration::from_millis(40));
+        assert_eq!(shared.now(), Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,385 @@
+use std::{error::Error, fmt, str::FromStr};
+
+/// Hue is meaningless below this chroma, so OKLCH interpolation takes the other color's hue
+const ACHROMATIC_CHROMA: f64 = 1e-4;
+
+/// sRGB color with straight alpha. every component is in `0..=1`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
+/// The space colors are blended in. sRGB is what a naive lerp does and gives dark, muddy
+/// midpoints; the OK spaces are perceptually uniform.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ColorSpace {
+    Srgb,
+    LinearRgb,
+    #[default]
+    Oklab,
+    /// polar OKLab, takes the shorter way around the hue wheel
+    Oklch,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0., 0., 0., 0.);
+    pub const BLACK: Color = Color::new(0., 0., 0., 1.);
+    pub const WHITE: Color = Color::new(1., 1., 1., 1.);
+
+    pub const fn new(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+        Self::from_rgba8(r, g, b, 1.)
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: f64) -> Self {
+        Self::new(r as f64 / 255., g as f64 / 255., b as f64 / 255., a)
+    }
+
+    /// hue in degrees, saturation and lightness in `0..=1`
+    pub fn from_hsla(hue: f64, saturation: f64, lightness: f64, a: f64) -> Self {
+        let hue = hue.rem_euclid(360.);
+        let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+        let x = chroma * (1. - ((hue / 60.) % 2. - 1.).abs());
+        let (r, g, b) = match hue {
+            h if h < 60. => (chroma, x, 0.),
+            h if h < 120. => (x, chroma, 0.),
+            h if h < 180. => (0., chroma, x),
+            h if h < 240. => (0., x, chroma),
+            h if h < 300. => (x, 0., chroma),
+            _ => (chroma, 0., x),
+        };
+        let m = lightness - chroma / 2.;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    /// accepts `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`, `hsla()`
+    /// and `transparent`, in both the comma and the space separated css syntax
+    pub fn parse(css: &str) -> Result<Self, ColorParseError> {
+        let input = css.trim().to_ascii_lowercase();
+        let error = || ColorParseError {
+            input: css.to_string(),
+        };
+        if input == "transparent" {
+            return Ok(Self::TRANSPARENT);
+        }
+        if let Some(hex) = input.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(error);
+        }
+        let (name, args) = input
+            .strip_suffix(')')
+            .and_then(|function| function.split_once('('))
+            .ok_or_else(error)?;
+        let args = parse_args(args).ok_or_else(error)?;
+        match name.trim() {
+            "rgb" | "rgba" => parse_rgb(&args),
+            "hsl" | "hsla" => parse_hsl(&args),
+            _ => None,
+        }
+        .ok_or_else(error)
+    }
+
+    pub fn to_css(&self) -> String {
+        let to_8bit = |channel: f64| (channel.clamp(0., 1.) * 255.).round() as u8;
+        format!(
+            "rgba({}, {}, {}, {})",
+            to_8bit(self.r),
+            to_8bit(self.g),
+            to_8bit(self.b),
+            self.a.clamp(0., 1.)
+        )
+    }
+
+    /// blends with premultiplied alpha in `space`, so fading from transparent doesn't tint
+    /// the midpoint. results are clipped to the sRGB gamut.
+    pub fn interpolate_in(&self, to: &Self, t: f64, space: ColorSpace) -> Self {
+        let alpha = (self.a + (to.a - self.a) * t).clamp(0., 1.);
+        if alpha == 0. {
+            return Self::TRANSPARENT;
+        }
+        let from_components = self.to_space(space);
+        let to_components = to.to_space(space);
+        let blend = |from: f64, to_component: f64| {
+            let from = from * self.a;
+            (from + (to_component * to.a - from) * t) / alpha
+        };
+        let mut blended = [
+            blend(from_components[0], to_components[0]),
+            blend(from_components[1], to_components[1]),
+            blend(from_components[2], to_components[2]),
+        ];
+        if space == ColorSpace::Oklch {
+            // hue isn't premultiplied
+            blended[2] = interpolate_hue(from_components, to_components, t);
+        }
+        Self::from_space(blended, alpha, space).clipped()
+    }
+
+    fn clipped(self) -> Self {
+        Self::new(
+            self.r.clamp(0., 1.),
+            self.g.clamp(0., 1.),
+            self.b.clamp(0., 1.),
+            self.a.clamp(0., 1.),
+        )
+    }
+
+    fn to_space(self, space: ColorSpace) -> [f64; 3] {
+        match space {
+            ColorSpace::Srgb => [self.r, self.g, self.b],
+            ColorSpace::LinearRgb => self.to_linear(),
+            ColorSpace::Oklab => linear_to_oklab(self.to_linear()),
+            ColorSpace::Oklch => oklab_to_oklch(linear_to_oklab(self.to_linear())),
+        }
+    }
+
+    fn from_space(components: [f64; 3], a: f64, space: ColorSpace) -> Self {
+        let [r, g, b] = match space {
+            ColorSpace::Srgb => components,
+            ColorSpace::LinearRgb => linear_to_srgb(components),
+            ColorSpace::Oklab => linear_to_srgb(oklab_to_linear(components)),
+            ColorSpace::Oklch => linear_to_srgb(oklab_to_linear(oklch_to_oklab(components))),
+        };
+        Self::new(r, g, b, a)
+    }
+
+    fn to_linear(self) -> [f64; 3] {
+        let decode = |c: f64| {
+            if c.abs() <= 0.04045 {
+                c / 12.92
+            } else {
+                c.signum() * ((c.abs() + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        [decode(self.r), decode(self.g), decode(self.b)]
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(css: &str) -> Result<Self, Self::Err> {
+        Self::parse(css)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorParseError {
+    input: String,
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported css color: {:?}", self.input)
+    }
+}
+
+impl Error for ColorParseError {}
+
+fn linear_to_srgb([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let encode = |c: f64| {
+        if c.abs() <= 0.0031308 {
+            c * 12.92
+        } else {
+            c.signum() * (1.055 * c.abs().powf(1. / 2.4) - 0.055)
+        }
+    };
+    [encode(r), encode(g), encode(b)]
+}
+
+fn linear_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn oklab_to_linear([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+}
+
+fn oklab_to_oklch([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    [
+        lightness,
+        a.hypot(b),
+        b.atan2(a).to_degrees().rem_euclid(360.),
+    ]
+}
+
+fn oklch_to_oklab([lightness, chroma, hue]: [f64; 3]) -> [f64; 3] {
+    let hue = hue.to_radians();
+    [lightness, chroma * hue.cos(), chroma * hue.sin()]
+}
+
+fn interpolate_hue(from: [f64; 3], to: [f64; 3], t: f64) -> f64 {
+    let (from_chroma, from_hue) = (from[1], from[2]);
+    let (to_chroma, to_hue) = (to[1], to[2]);
+    if from_chroma < ACHROMATIC_CHROMA {
+        return to_hue;
+    }
+    if to_chroma < ACHROMATIC_CHROMA {
+        return from_hue;
+    }
+    let delta = (to_hue - from_hue + 180.).rem_euclid(360.) - 180.;
+    (from_hue + delta * t).rem_euclid(360.)
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.is_ascii() {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|digit| u8::from_str_radix(&digit.to_string().repeat(2), 16).ok())
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let alpha = digits.get(3).map_or(1., |a| *a as f64 / 255.);
+    Some(Color::from_rgba8(digits[0], digits[1], digits[2], alpha))
+}
+
+/// splits `a, b, c, d`, `a b c` and `a b c / d` into components
+fn parse_args(args: &str) -> Option<Vec<&str>> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let mut components: Vec<&str> = if channels.contains(',') {
+        channels.split(',').map(str::trim).collect()
+    } else {
+        channels.split_whitespace().collect()
+    };
+    if let Some(alpha) = alpha {
+        if components.len() != 3 {
+            return None;
+        }
+        components.push(alpha);
+    }
+    matches!(components.len(), 3 | 4).then_some(components)
+}
+
+fn parse_number(component: &str) -> Option<f64> {
+    component
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+}
+
+/// `50%` or a plain number scaled by `max`, normalized to `0..=1`
+fn parse_unit(component: &str, max: f64) -> Option<f64> {
+    let value = match component.strip_suffix('%') {
+        Some(percent) => parse_number(percent)? / 100.,
+        None => parse_number(component)? / max,
+    };
+    Some(value.clamp(0., 1.))
+}
+
+fn parse_alpha(components: &[&str]) -> Option<f64> {
+    components
+        .get(3)
+        .map_or(Some(1.), |alpha| parse_unit(alpha, 1.))
+}
+
+fn parse_rgb(components: &[&str]) -> Option<Color> {
+    Some(Color::new(
+        parse_unit(components[0], 255.)?,
+        parse_unit(components[1], 255.)?,
+        parse_unit(components[2], 255.)?,
+        parse_alpha(components)?,
+    ))
+}
+
+fn parse_hsl(components: &[&str]) -> Option<Color> {
+    let hue = components[0];
+    let hue = match hue.strip_suffix("deg") {
+        Some(degrees) => parse_number(degrees)?,
+        None => match hue.strip_suffix("turn") {
+            Some(turns) => parse_number(turns)? * 360.,
+            None => parse_number(hue)?,
+        },
+    };
+    Some(Color::from_hsla(
+        hue,
+        parse_unit(components[1], 100.)?,
+        parse_unit(components[2], 100.)?,
+        parse_alpha(components)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_css_syntaxes() {
+        let orange = Color::from_rgb8(255, 136, 0);
+        assert_eq!(Color::parse("#ff8800"), Ok(orange));
+        assert_eq!(Color::parse("#F80"), Ok(orange));
+        assert_eq!(Color::parse("rgb(255, 136, 0)"), Ok(orange));
+        assert_eq!(
+            Color::parse("rgb(255 136 0 / 50%)"),
+            Ok(Color { a: 0.5, ..orange })
+        );
+        assert_eq!(
+            Color::parse("hsl(0, 100%, 50%)"),
+            Ok(Color::new(1., 0., 0., 1.))
+        );
+        assert_eq!(Color::parse("transparent"), Ok(Color::TRANSPARENT));
+        assert!(Color::parse("#ff88").is_ok());
+        assert!(Color::parse("#ff8").is_ok());
+        assert!(Color::parse("#ff88000").is_err());
+        assert!(Color::parse("rgb(1, 2)").is_err());
+        assert!(Color::parse("orange").is_err());
+    }
+
+    #[test]
+    fn fading_from_transparent_keeps_the_hue() {
+        let red = Color::new(1., 0., 0., 1.);
+        for space in [
+            ColorSpace::Srgb,
+            ColorSpace::LinearRgb,
+            ColorSpace::Oklab,
+            ColorSpace::Oklch,
+        ] {
+            let mid = Color::TRANSPARENT.interpolate_in(&red, 0.5, space);
+            assert!((mid.r - 1.).abs() < 1e-6, "{space:?}: {mid:?}");
+            assert!(
+                mid.g.abs() < 1e-6 && mid.b.abs() < 1e-6,
+                "{space:?}: {mid:?}"
+            );
+            assert_eq!(mid.a, 0.5);
+        }
+    }
+
+    #[test]
+    fn blends_end_on_their_targets() {
+        let from = Color::from_rgb8(255, 136, 0);
+        let to = Color::from_rgb8(0, 80, 255);
+        for space in [ColorSpace::Srgb, ColorSpace::Oklab, ColorSpace::Oklch] {
+            assert_eq!(from.interpolate_in(&to, 1., space).to_css(), to.to_css());
+            assert_eq!(from.interpolate_in(&to, 0., space).to_css(), from.to_css());
+        }
+    }
+}
@@ -1,20 +1,104 @@
-#[allow(non_snake_case)]
 use dioxus::prelude::*;
 
-use crate::hooks::UseFlipbook;
+use crate::{
+    controllers::{AnimationBuilder, AnimationEvent},
+    hooks::{FlipbookEventHandlers, LayoutRegistry, UseFlipbook},
+};
 
-const ANIMATABLE_BASE_STATE: &'static str = r#"
+const ANIMATABLE_BASE_STATE: &str = r#"
     display: flex;
     position: absolute; 
     box-sizing: border-box;
 "#;
 
+/// The lifecycle handlers receive the animation's id and the rect at the time of the event.
+/// `oncancel` fires for animations interrupted by `play_now` or `drop_all` after they started.
+///
+/// With `layout` the element stays in the page flow and animates FLIP style with that builder
+/// whenever a re-render of its parent moves or resizes it. The way there is drawn with a
+/// `transform`, so its siblings take the new layout right away. Positions are measured in the
+/// page, so scrolling the window doesn't animate. Layout changes that don't re-render the
+/// parent, like a sibling growing on its own, are picked up with
+/// [`UseFlipbook::request_layout`].
+///
+/// An `Animatable` mounting with the `layout_id` of one that unmounted in the same update
+/// animates from where that one was, with the `layout` builder or the default one. So does one
+/// switching to that `layout_id` while mounted. Needs a
+/// [`use_layout_registry`](crate::hooks::use_layout_registry) above both.
 #[component]
 pub fn Animatable(
     controller: Signal<UseFlipbook>,
     style: Option<String>,
+    layout: Option<AnimationBuilder>,
+    layout_id: Option<String>,
+    onstart: Option<EventHandler<AnimationEvent>>,
+    onframe: Option<EventHandler<AnimationEvent>>,
+    oncomplete: Option<EventHandler<AnimationEvent>>,
+    oncancel: Option<EventHandler<AnimationEvent>>,
     children: Element,
 ) -> Element {
+    // reruns when the props change, so the controller always calls the current handlers
+    controller
+        .peek()
+        .clone()
+        .set_event_handlers(FlipbookEventHandlers {
+            onstart,
+            onframe,
+            oncomplete,
+            oncancel,
+        });
+    controller
+        .peek()
+        .clone()
+        .set_layout_animation(layout.clone());
+    // a parent re-render hands down new children, even identical ones. renders for animation
+    // frames keep the same, so they don't measure
+    let mut last_children = use_hook(|| CopyValue::new(None as Element));
+    if *last_children.peek() != children {
+        last_children.set(children.clone());
+        controller.peek().clone().request_layout();
+    }
+    // measured once requested renders land, and again once a layout animation rests in case
+    // the layout changed during it
+    use_effect(move || {
+        let flipbook = controller.read();
+        flipbook.read_layout_requests();
+        if flipbook.read_is_finished() {
+            flipbook.measure_layout();
+        }
+    });
+
+    let registry = try_consume_context::<LayoutRegistry>();
+    let mut current_layout_id = use_hook(|| CopyValue::new(layout_id.clone()));
+    if *current_layout_id.peek() != layout_id {
+        // hand the old id's rect on, and take over the new one once whoever held it is gone
+        if let (Some(mut registry), Some(old)) = (registry, current_layout_id.peek().as_deref()) {
+            registry.release(old);
+        }
+        current_layout_id.set(layout_id.clone());
+        if let (Some(registry), Some(layout_id)) = (registry, layout_id) {
+            let layout = layout.clone();
+            spawn(async move {
+                enter_shared(controller, registry, &layout_id, layout);
+            });
+        }
+    }
+    use_drop(move || {
+        // the id may be gone already if the scope is torn down around it
+        let layout_id = current_layout_id.try_read().ok().and_then(|id| id.clone());
+        if let (Some(mut registry), Some(layout_id)) = (registry, layout_id) {
+            registry.release(&layout_id);
+        }
+    });
+    use_effect(move || {
+        let rect = controller.read().read_rect();
+        if let (Some(mut registry), Some(layout_id), Some(rect)) =
+            (registry, current_layout_id.peek().clone(), rect)
+        {
+            registry.record(layout_id, rect);
+        }
+    });
+
     let render_state = use_memo(move || {
         let mut state = controller.read().read_render_state();
         if let Some(style) = &style {
@@ -27,8 +111,30 @@ pub fn Animatable(
     rsx! {
         div {
             style: "{render_state}",
-            onmounted: move |cx| controller.write().set_mounted_data(cx.data()),
+            onmounted: move |cx| {
+                controller.write().set_mounted_data(cx.data());
+                controller.peek().measure_layout();
+                // mounted after the predecessor with the same id unmounted
+                if let (Some(registry), Some(layout_id)) = (registry, current_layout_id.peek().clone())
+                {
+                    enter_shared(controller, registry, &layout_id, layout.clone());
+                }
+            },
             {children}
         }
     }
 }
+
+/// animates from the rect another element left behind under `layout_id`, if any
+fn enter_shared(
+    controller: Signal<UseFlipbook>,
+    mut registry: LayoutRegistry,
+    layout_id: &str,
+    layout: Option<AnimationBuilder>,
+) {
+    if let Some(from) = registry.take(layout_id) {
+        controller
+            .peek()
+            .enter_from(from, layout.unwrap_or_default());
+    }
+}
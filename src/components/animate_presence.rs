@@ -0,0 +1,231 @@
+use dioxus::prelude::*;
+
+use crate::{controllers::AnimationBuilder, hooks::use_flipbook_signal};
+
+use super::Animatable;
+
+/// a child of [`AnimatePresence`], identified by `key` across renders
+#[derive(Clone, PartialEq)]
+pub struct PresenceItem {
+    pub key: String,
+    pub element: Element,
+}
+
+impl PresenceItem {
+    pub fn new(key: impl Into<String>, element: Element) -> Self {
+        Self {
+            key: key.into(),
+            element,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct PresenceEntry {
+    item: PresenceItem,
+    exiting: bool,
+}
+
+/// Renders each item in its own [`Animatable`] and keeps removed items around until their
+/// `exit` animation finishes. `enter` plays whenever an item mounts, including the items there
+/// on the first render, and again if an exiting item comes back.
+///
+/// Removed items stay where they were among the others while they exit. `layout` is passed on
+/// to every `Animatable`, so the remaining items glide into the space an exited one leaves.
+#[component]
+pub fn AnimatePresence(
+    items: Vec<PresenceItem>,
+    enter: Option<AnimationBuilder>,
+    exit: Option<AnimationBuilder>,
+    layout: Option<AnimationBuilder>,
+    style: Option<String>,
+) -> Element {
+    // not a signal: merged while rendering, and only an exit finishing re-renders
+    let mut entries = use_hook(|| CopyValue::new(Vec::<PresenceEntry>::new()));
+    let update = use_hook(schedule_update);
+
+    let merged = merge_presence(&entries.peek(), items);
+    entries.set(merged.clone());
+
+    rsx! {
+        for entry in merged {
+            PresenceSlot {
+                key: "{entry.item.key}",
+                element: entry.item.element,
+                exiting: entry.exiting,
+                enter: enter.clone(),
+                exit: exit.clone(),
+                layout: layout.clone(),
+                style: style.clone(),
+                onexited: {
+                    let key = entry.item.key.clone();
+                    let update = update.clone();
+                    move |_| {
+                        // nothing to remove if it came back before the exit finished
+                        let before = entries.peek().len();
+                        entries.write().retain(|entry| !(entry.exiting && entry.item.key == key));
+                        if entries.peek().len() != before {
+                            update();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// the new items in their order, with removed ones kept after the entry they followed
+fn merge_presence(previous: &[PresenceEntry], items: Vec<PresenceItem>) -> Vec<PresenceEntry> {
+    let mut merged: Vec<PresenceEntry> = items
+        .into_iter()
+        .map(|item| PresenceEntry {
+            item,
+            exiting: false,
+        })
+        .collect();
+    let mut anchor: Option<&str> = None;
+    for entry in previous {
+        let present = merged
+            .iter()
+            .any(|merged| !merged.exiting && merged.item.key == entry.item.key);
+        if !present {
+            let index = anchor
+                .and_then(|anchor| merged.iter().position(|merged| merged.item.key == anchor))
+                .map_or(0, |index| index + 1);
+            merged.insert(
+                index,
+                PresenceEntry {
+                    item: entry.item.clone(),
+                    exiting: true,
+                },
+            );
+        }
+        anchor = Some(&entry.item.key);
+    }
+    merged
+}
+
+/// Tags each exit, so one finishing after the item came back or after a later exit started
+/// doesn't remove the item early
+#[derive(Default)]
+struct Exits {
+    exiting: bool,
+    /// bumped on every change
+    generation: u64,
+}
+
+impl Exits {
+    /// whether `exiting` changed
+    fn set_exiting(&mut self, exiting: bool) -> bool {
+        if self.exiting == exiting {
+            return false;
+        }
+        self.exiting = exiting;
+        self.generation += 1;
+        true
+    }
+
+    fn is_current_exit(&self, generation: u64) -> bool {
+        self.exiting && self.generation == generation
+    }
+}
+
+#[component]
+fn PresenceSlot(
+    element: Element,
+    exiting: bool,
+    enter: Option<AnimationBuilder>,
+    exit: Option<AnimationBuilder>,
+    layout: Option<AnimationBuilder>,
+    style: Option<String>,
+    onexited: EventHandler<()>,
+) -> Element {
+    let controller = use_flipbook_signal();
+    // queued, so it waits for the rect read on mount
+    use_hook(|| {
+        if let Some(enter) = enter.clone() {
+            controller.peek().clone().queue(enter);
+        }
+    });
+
+    let mut exits = use_hook(|| CopyValue::new(Exits::default()));
+    if exits.write().set_exiting(exiting) {
+        if exiting {
+            let generation = exits.peek().generation;
+            let handle = exit.map(|exit| controller.peek().clone().play_now(exit));
+            spawn(async move {
+                if let Some(handle) = handle {
+                    handle.await;
+                }
+                // came back, and maybe left again, while this exit played
+                if exits.peek().is_current_exit(generation) {
+                    onexited.call(());
+                }
+            });
+        } else if let Some(enter) = enter {
+            controller.peek().clone().play_now(enter);
+        }
+    }
+
+    rsx! {
+        Animatable { controller, style, layout, {element} }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(keys: &[(&str, bool)]) -> Vec<PresenceEntry> {
+        keys.iter()
+            .map(|(key, exiting)| PresenceEntry {
+                item: PresenceItem::new(*key, None),
+                exiting: *exiting,
+            })
+            .collect()
+    }
+
+    fn keys(entries: &[PresenceEntry]) -> Vec<(String, bool)> {
+        entries
+            .iter()
+            .map(|entry| (entry.item.key.clone(), entry.exiting))
+            .collect()
+    }
+
+    #[test]
+    fn removed_items_keep_their_place_while_exiting() {
+        let previous = entries(&[("a", false), ("b", false), ("c", false)]);
+        let items = vec![PresenceItem::new("a", None), PresenceItem::new("c", None)];
+        assert_eq!(
+            keys(&merge_presence(&previous, items)),
+            keys(&entries(&[("a", false), ("b", true), ("c", false)]))
+        );
+    }
+
+    #[test]
+    fn items_coming_back_stop_exiting() {
+        let previous = entries(&[("a", false), ("b", true)]);
+        let items = vec![PresenceItem::new("a", None), PresenceItem::new("b", None)];
+        assert_eq!(
+            keys(&merge_presence(&previous, items)),
+            keys(&entries(&[("a", false), ("b", false)]))
+        );
+    }
+
+    #[test]
+    fn superseded_exits_are_stale() {
+        let mut exits = Exits::default();
+        assert!(!exits.set_exiting(false));
+
+        assert!(exits.set_exiting(true));
+        let first = exits.generation;
+        assert!(exits.is_current_exit(first));
+
+        // re-entered, then removed again before the first exit finished
+        assert!(exits.set_exiting(false));
+        assert!(!exits.is_current_exit(first));
+        assert!(exits.set_exiting(true));
+        assert!(!exits.is_current_exit(first));
+        assert!(exits.is_current_exit(exits.generation));
+    }
+}
@@ -1,2 +1,5 @@
 mod animatable;
 pub use animatable::*;
+
+mod animate_presence;
+pub use animate_presence::*;
@@ -1,64 +1,614 @@
-use crate::easing::Easing;
-use dioxus::html::geometry::euclid::Rect;
-use web_time::Duration;
+use crate::{easing::Easing, interpolate::Interpolate};
+use euclid::Rect;
 
-use super::AnimationBuilder;
+use super::{
+    resolve_keyframes, sample_keyframes, AnimationBuilder, Channels, DecayCurve, Iterations,
+    Keyframe, Spring, Timeline,
+};
 
 pub const MAX_RATE_60HZ: u64 = 60;
 pub const MAX_RATE_90HZ: u64 = 90;
 pub const MAX_RATE_120HZ: u64 = 120;
 
+/// finite difference used to estimate the velocity of an interrupted motion
+const MOMENTUM_SAMPLE_STEP: web_time::Duration = web_time::Duration::from_millis(1);
+
+/// The velocity of a motion at some instant, as the value it's at and the value it would reach
+/// one second later at that speed. Kept as values so any interpolatable type can carry it, as
+/// long as it extrapolates linearly. Colors clip, so their momentum comes out too weak.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Momentum<T: Interpolate> {
+    pub at: T,
+    pub ahead: T,
+}
+
+impl<T: Interpolate> Momentum<T> {
+    pub fn is_still(&self) -> bool {
+        self.at == self.ahead
+    }
+
+    /// `value` moved by `seconds` worth of this velocity. only interpolates, extrapolating
+    /// `value + (ahead - at) * seconds` through the midpoint of `value` and `ahead`
+    fn carry(&self, value: &T, seconds: f64) -> T {
+        let shifted = self
+            .at
+            .interpolate(&value.interpolate(&self.ahead, 0.5), 2.);
+        value.interpolate(&shifted, seconds)
+    }
+}
+
+/// The motion a transition cut off. The new transition keeps sampling it and crossfades
+/// into its own path, so the value and its velocity are continuous at the handoff.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Interrupted<T: Interpolate> {
+    pub transition: AnimationTransition<T>,
+    /// how far the interrupted transition had played when it was cut off
+    pub elapsed: web_time::Duration,
+    pub blend: web_time::Duration,
+}
+
+impl<T: Interpolate> Interrupted<T> {
+    /// 0 at the handoff, 1 once the blend is over. smoothstep, so the weight
+    /// adds no velocity of its own at either end
+    fn weight_at(&self, elapsed: web_time::Duration) -> Option<f64> {
+        if elapsed >= self.blend {
+            return None;
+        }
+        let t = elapsed.as_secs_f64() / self.blend.as_secs_f64();
+        Some(t * t * (3. - 2. * t))
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
-pub struct AnimationTransition {
-    pub from: Rect<f64, f64>,
-    pub to: Rect<f64, f64>,
+pub struct AnimationTransition<T: Interpolate = Rect<f64, f64>> {
+    pub from: T,
+    pub to: T,
+    pub channels_from: Channels,
+    pub channels_to: Channels,
+    /// empty for a direct `from` to `to` transition
+    pub keyframes: Vec<Keyframe<T>>,
     pub easing: Easing,
+    /// replaces `easing` and sets the duration to the spring's settle time
+    pub spring: Option<Spring>,
+    /// replaces `easing` and `spring`, the duration is the time it takes to come to rest
+    pub decay: Option<DecayCurve>,
+    /// how long to blend out of a transition this one interrupts
+    pub interrupt_blend: Option<web_time::Duration>,
+    pub interrupted: Option<Box<Interrupted<T>>>,
+    /// velocity inherited from an interrupted motion. springs carry it on, see [`Self::carrying`]
+    pub momentum: Option<Momentum<T>>,
+    pub iterations: Iterations,
+    pub alternate: bool,
+    pub fps_cap: Option<u64>,
+    /// of a single iteration
     duration: web_time::Duration,
-    min_frame_duration: web_time::Duration,
-    linear_progress: f32,
+    elapsed: web_time::Duration,
+    /// set while playing backwards, to the playhead the reversed run is mirrored from
+    reversed: Option<web_time::Duration>,
 }
 
-impl AnimationTransition {
-    pub fn new(builder: AnimationBuilder, from: Rect<f64, f64>, to: Rect<f64, f64>) -> Self {
-        let min_frame_duration = Self::get_frame_duration_from_refresh_rate(builder.fps_cap);
+impl<T: Interpolate> AnimationTransition<T> {
+    pub fn new(builder: AnimationBuilder<T>, from: T, to: T) -> Self {
+        let keyframes = resolve_keyframes(builder.keyframes, &from, &to, &builder.easing);
+        let spring = builder.spring.map(|config| Spring::new(config, 0.));
+        let decay = builder.decay.map(|decay| decay.resolve(&from).1);
+        let duration = match (&decay, &spring) {
+            (Some(decay), _) => decay.duration(),
+            (None, Some(spring)) => spring.settle_duration(),
+            (None, None) => builder.duration,
+        };
+        let interrupt_blend = builder.interrupt_blend.map(|blend| blend.min(duration));
         Self {
             from,
             to,
+            channels_from: Channels::default(),
+            channels_to: Channels::default(),
+            keyframes,
             easing: builder.easing,
-            duration: builder.duration,
-            min_frame_duration,
-            linear_progress: 0f32,
+            spring,
+            decay,
+            interrupt_blend,
+            interrupted: None,
+            momentum: None,
+            iterations: builder.iterations,
+            alternate: builder.alternate,
+            fps_cap: builder.fps_cap,
+            duration,
+            elapsed: web_time::Duration::ZERO,
+            reversed: None,
         }
     }
 
-    fn get_frame_duration_from_refresh_rate(max_refresh_rate: u64) -> Duration {
-        Duration::from_millis(1000 / max_refresh_rate)
+    /// resolves `builder` against the value and channels it starts from. `None` without
+    /// a known origin or when there is nothing to animate
+    pub fn resolve(
+        builder: AnimationBuilder<T>,
+        value: Option<&T>,
+        channels: &Channels,
+    ) -> Option<Self> {
+        Self::resolve_endpoints(builder, value, channels).filter(|transition| {
+            if transition.is_still() {
+                tracing::error!(
+                    "requested animation has same origin and destination: {:?} {:?}",
+                    transition.from,
+                    transition.to
+                );
+            }
+            !transition.is_still()
+        })
+    }
+
+    /// like [`Self::resolve`], but keeps transitions that go nowhere. a spring retargeted
+    /// onto the value it's at still has momentum to work off
+    pub(crate) fn resolve_endpoints(
+        builder: AnimationBuilder<T>,
+        value: Option<&T>,
+        channels: &Channels,
+    ) -> Option<Self> {
+        // keyframes at the very start and end are the endpoints, so the path and the value
+        // the transition settles on agree
+        let stop_at = |offset: f64| {
+            builder
+                .keyframes
+                .iter()
+                .rev()
+                .find(|keyframe| keyframe.offset == offset)
+                .map(|keyframe| keyframe.value.clone())
+        };
+        let from = stop_at(0.)
+            .or_else(|| builder.from.clone())
+            .or_else(|| value.cloned())?;
+        // flings stop where the physics says, keyframe paths end on their last stop,
+        // channel-only animations hold the value
+        let to = builder
+            .decay
+            .as_ref()
+            .map(|decay| decay.resolve(&from).0)
+            .or_else(|| stop_at(1.))
+            .or_else(|| builder.to.clone())
+            .or_else(|| {
+                builder
+                    .keyframes
+                    .iter()
+                    .max_by(|a, b| a.offset.total_cmp(&b.offset))
+                    .map(|keyframe| keyframe.value.clone())
+            })
+            .unwrap_or_else(|| from.clone());
+        let (channels_from, channels_to) = builder.channels.resolve(channels);
+        Some(Self::new(builder, from, to).with_channels(channels_from, channels_to))
     }
 
-    pub async fn step(&mut self, total_elapsed: web_time::Duration) -> Rect<f64, f64> {
-        let frame_start = web_time::SystemTime::now();
+    /// nothing moves: no distance, no path, no channel change and no momentum
+    pub fn is_still(&self) -> bool {
+        self.from == self.to
+            && self.keyframes.is_empty()
+            && self.channels_from == self.channels_to
+            && self.momentum.is_none()
+    }
+
+    /// the velocity at `elapsed`, estimated from samples a millisecond apart
+    pub fn momentum_at(&self, elapsed: web_time::Duration) -> Momentum<T> {
+        let per_second = 1. / MOMENTUM_SAMPLE_STEP.as_secs_f64();
+        let at = self.sample(elapsed);
+        let ahead = match elapsed.checked_sub(MOMENTUM_SAMPLE_STEP) {
+            Some(before) => self.sample(before).interpolate(&at, 1. + per_second),
+            None => at.interpolate(&self.sample(elapsed + MOMENTUM_SAMPLE_STEP), per_second),
+        };
+        Momentum { at, ahead }
+    }
 
-        self.linear_progress =
-            (total_elapsed.as_secs_f64() / self.duration.as_secs_f64()).clamp(0., 1.) as f32;
-        let interpolated_progress = self.easing.ease(self.linear_progress) as f64;
+    /// A spring starts out moving with `momentum` and settles the way a physical one would,
+    /// so retargeting mid-flight has no kink. Other transitions start from rest, unless they
+    /// blend out of the interrupted motion.
+    pub fn carrying(mut self, momentum: Momentum<T>) -> Self {
+        if momentum.is_still() || self.decay.is_some() {
+            return self;
+        }
+        let Some(spring) = self.spring.take() else {
+            return self;
+        };
+        let spring = spring.carrying_momentum();
+        self.duration = spring.settle_duration();
+        self.interrupt_blend = self.interrupt_blend.map(|blend| blend.min(self.duration));
+        self.spring = Some(spring);
+        self.momentum = Some(momentum);
+        self
+    }
 
-        let current_rect = self.from.lerp(self.to, interpolated_progress);
+    pub fn with_channels(mut self, from: Channels, to: Channels) -> Self {
+        self.channels_from = from;
+        self.channels_to = to;
+        self
+    }
 
-        let frame_duration = frame_start
-            .elapsed()
-            .expect("couldn't get elapsed time during frame");
+    /// continue out of `interrupted`, cut off `elapsed` into its run, if this transition
+    /// blends interruptions
+    pub fn interrupting(
+        mut self,
+        mut interrupted: AnimationTransition<T>,
+        elapsed: web_time::Duration,
+    ) -> Self {
+        let Some(blend) = self.interrupt_blend.filter(|blend| !blend.is_zero()) else {
+            return self;
+        };
+        // a blend that already finished no longer affects the interrupted motion
+        if interrupted
+            .interrupted
+            .as_ref()
+            .is_some_and(|nested| elapsed >= nested.blend)
+        {
+            interrupted.interrupted = None;
+        }
+        self.interrupted = Some(Box::new(Interrupted {
+            transition: interrupted,
+            elapsed,
+            blend,
+        }));
+        self
+    }
 
-        if frame_duration < self.min_frame_duration {
-            let delay_duration = self.min_frame_duration.as_millis() - frame_duration.as_millis();
-            let delay =
-                gloo_timers::future::sleep(web_time::Duration::from_millis(delay_duration as u64));
-            delay.await;
+    fn eased_progress_at(&self, elapsed: web_time::Duration) -> f64 {
+        match (&self.decay, &self.spring) {
+            (Some(decay), _) => decay.position(elapsed),
+            (None, Some(spring)) => spring.position(elapsed.min(self.duration)),
+            (None, None) => self.easing.ease(self.linear_progress_at(elapsed)) as f64,
         }
-        tracing::info!("animation frame exec: {:?}", &current_rect);
-        current_rect
+    }
+
+    /// where `elapsed` lands on the forward run of all iterations. mirrored while reversed
+    fn playhead_at(&self, elapsed: web_time::Duration) -> web_time::Duration {
+        match self.reversed {
+            Some(mirror) => mirror.saturating_sub(elapsed.min(self.duration())),
+            None => elapsed,
+        }
+    }
+
+    /// the elapsed time `playhead` into the forward run lands on, whichever way this plays
+    pub fn time_at(&self, playhead: web_time::Duration) -> web_time::Duration {
+        match self.reversed {
+            Some(mirror) => mirror.saturating_sub(playhead).min(self.duration()),
+            None => playhead,
+        }
+    }
+
+    /// where a reversed run mirrored from `mirror` stops. a loop only goes back to the start of
+    /// the iteration it was reversed in
+    fn reversed_end(&self, mirror: web_time::Duration) -> web_time::Duration {
+        match self.iterations {
+            Iterations::Count(_) => web_time::Duration::ZERO,
+            Iterations::Infinite => mirror.saturating_sub(self.duration),
+        }
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed.is_some()
+    }
+
+    /// flips the direction of play at the last step. the way back takes as long as the way
+    /// there did and passes through the same eased values, so the easing stays symmetric.
+    /// returns the elapsed time to continue from
+    pub fn reverse(&mut self) -> web_time::Duration {
+        let playhead = self.playhead_at(self.elapsed);
+        // the blend window is measured from the start, so it can't survive the jump in time
+        self.interrupted = None;
+        self.reversed = match self.reversed {
+            Some(_) => None,
+            // a loop plays back to the start of the iteration it is in
+            None => Some(match self.iterations {
+                Iterations::Count(_) => self.forward_duration(),
+                Iterations::Infinite => {
+                    let (iteration, _) = self.iteration_at(playhead);
+                    self.duration * (iteration + 1)
+                }
+            }),
+        };
+        self.elapsed = self.time_at(playhead);
+        self.elapsed
+    }
+
+    /// all iterations played forward
+    fn forward_duration(&self) -> web_time::Duration {
+        match self.iterations {
+            Iterations::Count(count) => self
+                .duration
+                .checked_mul(count.max(1))
+                .unwrap_or(web_time::Duration::MAX),
+            Iterations::Infinite if self.duration.is_zero() => self.duration,
+            Iterations::Infinite => web_time::Duration::MAX,
+        }
+    }
+
+    /// the iteration playing at `elapsed` and how far into it. the last iteration holds its end
+    fn iteration_at(&self, elapsed: web_time::Duration) -> (u32, web_time::Duration) {
+        if self.duration.is_zero() {
+            return (0, elapsed);
+        }
+        let iteration = (elapsed.as_secs_f64() / self.duration.as_secs_f64()) as u32;
+        let iteration = match self.iterations {
+            Iterations::Count(count) => iteration.min(count.max(1) - 1),
+            Iterations::Infinite => iteration,
+        };
+        (iteration, elapsed.saturating_sub(self.duration * iteration))
+    }
+
+    /// time into a single forward run of the transition. alternate iterations run it backwards
+    fn position_at(&self, elapsed: web_time::Duration) -> web_time::Duration {
+        let (iteration, local) = self.iteration_at(self.playhead_at(elapsed));
+        if self.alternate && iteration % 2 == 1 {
+            self.duration.saturating_sub(local)
+        } else {
+            local
+        }
+    }
+
+    /// counts from 0
+    pub fn iteration(&self) -> u32 {
+        self.iteration_at(self.playhead_at(self.elapsed)).0
+    }
+
+    /// linear `0..=1` over all iterations as of the last step. transitions repeating forever
+    /// report the progress of the current iteration
+    pub fn progress(&self) -> f64 {
+        let playhead = self.playhead_at(self.elapsed);
+        match self.iterations {
+            Iterations::Count(_) => {
+                let total = self.forward_duration();
+                if total.is_zero() {
+                    return 1.;
+                }
+                (playhead.as_secs_f64() / total.as_secs_f64()).clamp(0., 1.)
+            }
+            Iterations::Infinite => self.linear_progress_at(self.iteration_at(playhead).1) as f64,
+        }
+    }
+
+    /// the elapsed time where [`Self::progress`] would read `progress`
+    pub fn time_at_progress(&self, progress: f64) -> web_time::Duration {
+        if progress.is_nan() {
+            tracing::error!("no time at progress {}, staying put", progress);
+            return self.elapsed;
+        }
+        let progress = progress.clamp(0., 1.);
+        let playhead = match self.iterations {
+            Iterations::Count(_) => self.forward_duration().mul_f64(progress),
+            Iterations::Infinite => {
+                let (iteration, _) = self.iteration_at(self.playhead_at(self.elapsed));
+                self.duration * iteration + self.duration.mul_f64(progress)
+            }
+        };
+        self.time_at(playhead)
+    }
+
+    /// whether play stops on `from`: the last of an even number of alternating iterations does,
+    /// so does reversing back to the start of any forward pass
+    fn ends_on_from(&self) -> bool {
+        match self.reversed {
+            Some(mirror) => {
+                let (iteration, _) = self.iteration_at(self.reversed_end(mirror));
+                !self.alternate || iteration % 2 == 0
+            }
+            None => {
+                self.alternate
+                    && matches!(self.iterations, Iterations::Count(count) if count.max(1) % 2 == 0)
+            }
+        }
+    }
+
+    /// where the last iteration stops, or where it started when played in reverse
+    pub fn final_value(&self) -> &T {
+        if self.ends_on_from() {
+            &self.from
+        } else {
+            &self.to
+        }
+    }
+
+    pub fn final_channels(&self) -> &Channels {
+        if self.ends_on_from() {
+            &self.channels_from
+        } else {
+            &self.channels_to
+        }
+    }
+
+    pub fn sample_channels(&self, elapsed: web_time::Duration) -> Channels {
+        let position = self.position_at(elapsed);
+        let channels = self
+            .channels_from
+            .interpolate(&self.channels_to, self.eased_progress_at(position));
+        match &self.interrupted {
+            Some(interrupted) => match interrupted.weight_at(elapsed) {
+                Some(weight) => interrupted
+                    .transition
+                    .sample_channels(interrupted.elapsed + elapsed)
+                    .interpolate(&channels, weight),
+                None => channels,
+            },
+            None => channels,
+        }
+    }
+
+    fn sample_own(&self, elapsed: web_time::Duration) -> T {
+        let position = self.position_at(elapsed);
+        if !self.keyframes.is_empty() {
+            // keyframe segments carry their own easing
+            return sample_keyframes(&self.keyframes, self.linear_progress_at(position) as f64);
+        }
+        let value = self
+            .from
+            .interpolate(&self.to, self.eased_progress_at(position));
+        match (&self.momentum, &self.spring) {
+            (Some(momentum), Some(spring)) => {
+                momentum.carry(&value, spring.momentum_displacement(position))
+            }
+            _ => value,
+        }
+    }
+
+    fn linear_progress_at(&self, elapsed: web_time::Duration) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).clamp(0., 1.) as f32
+    }
+
+    /// frame pacing is left to the caller
+    pub fn step(&mut self, total_elapsed: web_time::Duration) -> T {
+        self.elapsed = total_elapsed;
+        let current_value = self.sample(total_elapsed);
+
+        tracing::info!("animation frame exec: {:?}", &current_value);
+        current_value
     }
 
     pub fn is_finished(&self) -> bool {
-        self.linear_progress >= 1.0
+        self.is_finished_at(self.elapsed)
+    }
+}
+
+impl<T: Interpolate> Timeline for AnimationTransition<T> {
+    type Value = T;
+
+    fn sample(&self, elapsed: web_time::Duration) -> T {
+        let value = self.sample_own(elapsed);
+        match &self.interrupted {
+            Some(interrupted) => match interrupted.weight_at(elapsed) {
+                Some(weight) => interrupted
+                    .transition
+                    .sample(interrupted.elapsed + elapsed)
+                    .interpolate(&value, weight),
+                None => value,
+            },
+            None => value,
+        }
+    }
+
+    /// all iterations. repeating forever never ends, unless reversed back to the start of
+    /// the current iteration
+    fn duration(&self) -> web_time::Duration {
+        match self.reversed {
+            Some(mirror) => mirror.saturating_sub(self.reversed_end(mirror)),
+            None => self.forward_duration(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use web_time::Duration;
+
+    use super::*;
+
+    fn transition(builder: AnimationBuilder<f64>) -> AnimationTransition<f64> {
+        let builder = builder
+            .with_duration(Duration::from_millis(1000))
+            .with_easing(Easing::Linear);
+        AnimationTransition::resolve(builder, Some(&0.), &Channels::default()).unwrap()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn samples_in_any_order_without_stepping() {
+        let transition = transition(AnimationBuilder::default().animate_to(100.));
+        assert_eq!(transition.sample(ms(750)), 75.);
+        assert_eq!(transition.sample(ms(250)), 25.);
+        assert_eq!(transition.sample(ms(5000)), 100.);
+        assert_eq!(transition.duration(), ms(1000));
+        assert!(transition.is_finished_at(ms(1000)));
+    }
+
+    #[test]
+    fn time_at_nan_progress_stays_put() {
+        let mut transition = transition(AnimationBuilder::default().animate_to(100.));
+        transition.step(ms(300));
+        assert_eq!(transition.time_at_progress(f64::NAN), ms(300));
+        assert_eq!(transition.time_at_progress(2.), ms(1000));
+    }
+
+    #[test]
+    fn nothing_to_animate_resolves_to_none() {
+        let builder = AnimationBuilder::default().animate_to(0.);
+        assert_eq!(
+            AnimationTransition::resolve(builder, Some(&0.), &Channels::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn a_final_keyframe_is_the_destination() {
+        let builder = AnimationBuilder::default()
+            .animate_to(100.)
+            .keyframe(Keyframe::new(0.5, 80.))
+            .keyframe(Keyframe::new(1., 50.));
+        let transition = transition(builder);
+        assert_eq!(transition.to, 50.);
+        assert_eq!(transition.sample(ms(999)).round(), 50.);
+        assert_eq!(transition.sample(ms(1000)), 50.);
+        assert_eq!(transition.final_value(), &50.);
+    }
+
+    #[test]
+    fn keyframes_fill_in_the_endpoints() {
+        let transition = transition(
+            AnimationBuilder::default()
+                .animate_to(100.)
+                .keyframe(Keyframe::new(0.5, 20.)),
+        );
+        assert_eq!(transition.sample(ms(0)), 0.);
+        assert_eq!(transition.sample(ms(500)), 20.);
+        assert_eq!(transition.sample(ms(750)), 60.);
+        assert_eq!(transition.sample(ms(1000)), 100.);
+    }
+
+    #[test]
+    fn alternate_repeats_play_every_other_pass_backwards() {
+        let transition = transition(
+            AnimationBuilder::default()
+                .animate_to(100.)
+                .repeat(2)
+                .alternate(),
+        );
+        assert_eq!(transition.duration(), ms(2000));
+        assert_eq!(transition.sample(ms(250)), 25.);
+        assert_eq!(transition.sample(ms(1250)), 75.);
+        assert_eq!(transition.final_value(), &0.);
+    }
+
+    #[test]
+    fn reverse_retraces_the_way_there() {
+        let mut transition = transition(AnimationBuilder::default().animate_to(100.));
+        transition.step(ms(400));
+        let elapsed = transition.reverse();
+        assert_eq!(elapsed, ms(600));
+        // progress goes through f32 for the easing
+        assert!((transition.sample(ms(600)) - 40.).abs() < 1e-4);
+        assert_eq!(transition.sample(ms(1000)), 0.);
+        assert_eq!(transition.duration(), ms(1000));
+        assert_eq!(transition.final_value(), &0.);
+    }
+
+    #[test]
+    fn reversing_a_loop_stops_at_the_start_of_its_iteration() {
+        let mut transition = transition(
+            AnimationBuilder::default()
+                .animate_to(100.)
+                .repeat_forever(),
+        );
+        transition.step(ms(2400));
+        assert_eq!(transition.iteration(), 2);
+        let elapsed = transition.reverse();
+        assert_eq!(elapsed, ms(600));
+        assert_eq!(transition.duration(), ms(1000));
+        assert!((transition.sample(ms(600)) - 40.).abs() < 1e-4);
+        assert_eq!(transition.sample(ms(1000)), 0.);
+        // doesn't rewind through the earlier iterations
+        assert_eq!(transition.sample(ms(1500)), 0.);
+        transition.step(ms(1000));
+        assert!(transition.is_finished());
+        assert_eq!(transition.iteration(), 2);
+        assert_eq!(transition.final_value(), &0.);
     }
 }
@@ -1,45 +1,256 @@
-use dioxus::html::geometry::euclid::Rect;
+use euclid::Rect;
 
-use crate::easing::Easing;
+use crate::{
+    color::{Color, ColorSpace},
+    css::{is_css_property_name, CssLength},
+    easing::Easing,
+    interpolate::Interpolate,
+};
 
-use super::{MAX_RATE_120HZ, MAX_RATE_60HZ, MAX_RATE_90HZ};
+use super::{
+    ChannelTarget, ChannelTargets, Decay, DecayConfig, DecayCurve, DecayTarget, Keyframe, Spring,
+    SpringConfig, MAX_RATE_120HZ, MAX_RATE_60HZ, MAX_RATE_90HZ,
+};
+
+/// how many times a transition plays, like css `animation-iteration-count`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Iterations {
+    Count(u32),
+    Infinite,
+}
+
+impl Default for Iterations {
+    fn default() -> Self {
+        Self::Count(1)
+    }
+}
+
+/// What an incoming animation does to the ones already running or queued
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum InterruptPolicy {
+    /// cancels the running animation and the queue, like `play_now`
+    #[default]
+    Replace,
+    /// waits for everything queued, like `queue`
+    Append,
+    /// cancels only the running animation, the queue plays on afterwards
+    ReplaceAndKeepQueue,
+    /// dropped if anything is running or queued
+    IgnoreWhileBusy,
+    /// replaces, crossfading out of the interrupted motion over this long
+    Blend(web_time::Duration),
+}
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct AnimationBuilder {
-    pub from: Option<Rect<f64, f64>>,
-    pub to: Option<Rect<f64, f64>>,
+pub struct AnimationBuilder<T: Interpolate = Rect<f64, f64>> {
+    pub from: Option<T>,
+    pub to: Option<T>,
+    pub channels: ChannelTargets,
+    pub keyframes: Vec<Keyframe<T>>,
     pub duration: web_time::Duration,
     pub easing: Easing,
-    pub fps_cap: u64,
+    /// when set, replaces `duration` and `easing`. the transition ends once the spring settles.
+    /// keyframe paths only take the settle time, they ease per segment
+    pub spring: Option<SpringConfig>,
+    /// when set, the value coasts to a stop instead of heading for `to`
+    pub decay: Option<DecayTarget<T>>,
+    /// when this interrupts a running transition through `play_now`, blend out of the
+    /// interrupted motion over this long instead of starting from rest
+    pub interrupt_blend: Option<web_time::Duration>,
+    /// how `play` treats what's already running. `None` defers to the controller
+    pub interrupt_policy: Option<InterruptPolicy>,
+    pub iterations: Iterations,
+    /// every other iteration plays backwards, easing included
+    pub alternate: bool,
+    /// optional ceiling on top of the display refresh rate. `None` renders every frame
+    pub fps_cap: Option<u64>,
 }
 
-impl Default for AnimationBuilder {
+impl<T: Interpolate> Default for AnimationBuilder<T> {
     fn default() -> Self {
         Self {
             from: None,
             to: None,
+            channels: ChannelTargets::default(),
+            keyframes: Vec::new(),
             duration: web_time::Duration::from_millis(1000),
             easing: Easing::SineInOut,
-            fps_cap: MAX_RATE_60HZ,
+            spring: None,
+            decay: None,
+            interrupt_blend: None,
+            interrupt_policy: None,
+            iterations: Iterations::default(),
+            alternate: false,
+            fps_cap: None,
         }
     }
 }
 
-impl AnimationBuilder {
+impl<T: Interpolate> AnimationBuilder<T> {
     pub fn new_delay(delay: web_time::Duration) -> Self {
         Self::default().with_duration(delay)
     }
 
-    pub fn animate_from(mut self, from: Rect<f64, f64>) -> Self {
+    pub fn animate_from(mut self, from: T) -> Self {
         self.from = Some(from);
         self
     }
 
-    pub fn animate_to(mut self, to: Rect<f64, f64>) -> Self {
+    pub fn animate_to(mut self, to: T) -> Self {
         self.to = Some(to);
         self
     }
 
+    /// adds a stop between `from` and `to`. all keyframes play as a single transition over
+    /// `duration`. without `animate_to` the last keyframe is the destination. keyframes at
+    /// offset `0.0` and `1.0` take precedence over `animate_from` and `animate_to`
+    pub fn keyframe(mut self, keyframe: Keyframe<T>) -> Self {
+        self.keyframes.push(keyframe);
+        self
+    }
+
+    /// starting opacity, `0.0` to `1.0`. without `fade_to` it fades back to the current opacity
+    pub fn fade_from(mut self, opacity: f64) -> Self {
+        self.channels.opacity.from = Some(opacity);
+        self
+    }
+
+    pub fn fade_to(mut self, opacity: f64) -> Self {
+        self.channels.opacity.to = Some(opacity);
+        self
+    }
+
+    /// degrees, clockwise
+    pub fn rotate_from(mut self, degrees: f64) -> Self {
+        self.channels.rotate.from = Some(degrees);
+        self
+    }
+
+    /// degrees, clockwise. rotation is applied as a transform so layout size is unchanged
+    pub fn rotate_to(mut self, degrees: f64) -> Self {
+        self.channels.rotate.to = Some(degrees);
+        self
+    }
+
+    pub fn scale_from(mut self, scale: f64) -> Self {
+        self.channels.scale.from = Some((scale, scale));
+        self
+    }
+
+    pub fn scale_to(mut self, scale: f64) -> Self {
+        self.channels.scale.to = Some((scale, scale));
+        self
+    }
+
+    pub fn scale_xy_to(mut self, x: f64, y: f64) -> Self {
+        self.channels.scale.to = Some((x, y));
+        self
+    }
+
+    /// x and y angles in degrees
+    pub fn skew_from(mut self, x: f64, y: f64) -> Self {
+        self.channels.skew.from = Some((x, y));
+        self
+    }
+
+    /// x and y angles in degrees
+    pub fn skew_to(mut self, x: f64, y: f64) -> Self {
+        self.channels.skew.to = Some((x, y));
+        self
+    }
+
+    pub fn background_color_from(mut self, color: Color) -> Self {
+        self.channels.background_color.from = Some(color);
+        self
+    }
+
+    /// use [`Color::parse`] for css color strings
+    pub fn background_color_to(mut self, color: Color) -> Self {
+        self.channels.background_color.to = Some(color);
+        self
+    }
+
+    pub fn border_color_from(mut self, color: Color) -> Self {
+        self.channels.border_color.from = Some(color);
+        self
+    }
+
+    pub fn border_color_to(mut self, color: Color) -> Self {
+        self.channels.border_color.to = Some(color);
+        self
+    }
+
+    pub fn text_color_from(mut self, color: Color) -> Self {
+        self.channels.text_color.from = Some(color);
+        self
+    }
+
+    pub fn text_color_to(mut self, color: Color) -> Self {
+        self.channels.text_color.to = Some(color);
+        self
+    }
+
+    /// defaults to [`ColorSpace::Oklab`]
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.channels.color_space = color_space;
+        self
+    }
+
+    /// animates a numeric css property such as `padding`, `font-size` or `border-width`.
+    /// names that aren't css identifiers are ignored, so they can't break the style
+    pub fn animate_property(self, name: impl Into<String>, from: CssLength, to: CssLength) -> Self {
+        self.with_property(name.into(), Some(from), to)
+    }
+
+    /// starts from the property's last animated value, or zero if it was never animated
+    pub fn animate_property_to(self, name: impl Into<String>, to: CssLength) -> Self {
+        self.with_property(name.into(), None, to)
+    }
+
+    fn with_property(mut self, name: String, from: Option<CssLength>, to: CssLength) -> Self {
+        if !is_css_property_name(&name) {
+            tracing::error!("ignoring invalid css property name {:?}", name);
+            return self;
+        }
+        self.channels
+            .properties
+            .insert(name, ChannelTarget { from, to: Some(to) });
+        self
+    }
+
+    /// builders without a destination or any channel target are delays
+    pub fn is_delay(&self) -> bool {
+        self.to.is_none()
+            && self.decay.is_none()
+            && self.keyframes.is_empty()
+            && !self.channels.is_set()
+    }
+
+    /// how long this plays, all iterations included, before it's resolved against a
+    /// starting value. flings are assumed not to hit their bounds
+    pub fn planned_duration(&self) -> web_time::Duration {
+        let duration = match (&self.decay, &self.spring) {
+            (Some(decay), _) => {
+                let config = &decay.config;
+                DecayCurve::new(
+                    config.friction,
+                    config.velocity.length(),
+                    config.rest_velocity,
+                )
+                .duration()
+            }
+            (None, Some(config)) => Spring::new(config.clone(), 0.).settle_duration(),
+            (None, None) => self.duration,
+        };
+        match self.iterations {
+            Iterations::Count(count) => duration
+                .checked_mul(count.max(1))
+                .unwrap_or(web_time::Duration::MAX),
+            Iterations::Infinite if duration.is_zero() => duration,
+            Iterations::Infinite => web_time::Duration::MAX,
+        }
+    }
+
     pub fn with_duration(mut self, duration: web_time::Duration) -> Self {
         self.duration = duration;
         self
@@ -50,13 +261,98 @@ impl AnimationBuilder {
         self
     }
 
+    /// physics-based motion instead of `duration` + `easing`. keyframes keep their own
+    /// easing per segment, with a spring they only take its settle time as their duration
+    pub fn spring(mut self, config: SpringConfig) -> Self {
+        self.spring = Some(config);
+        self
+    }
+
+    /// keeps retargeting smooth: the interrupted motion carries on and crossfades into this one.
+    /// springs don't need it, they take over the interrupted velocity
+    pub fn blend_interruptions(mut self, blend: web_time::Duration) -> Self {
+        self.interrupt_blend = Some(blend);
+        self
+    }
+
+    /// plays `count` times in total, at least once. the queue moves on after the last iteration
+    pub fn repeat(mut self, count: u32) -> Self {
+        if count == 0 {
+            tracing::error!("ignoring repeat count of 0");
+            return self;
+        }
+        self.iterations = Iterations::Count(count);
+        self
+    }
+
+    /// loops until interrupted with `play_now` or `drop_all`
+    pub fn repeat_forever(mut self) -> Self {
+        self.iterations = Iterations::Infinite;
+        self
+    }
+
+    /// yoyo: iterations go back and forth between `from` and `to`. needs `repeat` to go back
+    pub fn alternate(mut self) -> Self {
+        self.alternate = true;
+        self
+    }
+
+    /// overrides the controller's policy when this is started with `play`
+    pub fn with_interrupt_policy(mut self, policy: InterruptPolicy) -> Self {
+        self.interrupt_policy = Some(policy);
+        self
+    }
+
+    /// frames per second, at least 1
+    pub fn with_fps_cap(mut self, fps_cap: u64) -> Self {
+        if fps_cap == 0 {
+            tracing::error!("ignoring fps cap of 0");
+            return self;
+        }
+        self.fps_cap = Some(fps_cap);
+        self
+    }
+
+    pub fn at_max_60hz(mut self) -> Self {
+        self.fps_cap = Some(MAX_RATE_60HZ);
+        self
+    }
+
     pub fn at_max_90hz(mut self) -> Self {
-        self.fps_cap = MAX_RATE_90HZ;
+        self.fps_cap = Some(MAX_RATE_90HZ);
         self
     }
 
     pub fn at_max_120hz(mut self) -> Self {
-        self.fps_cap = MAX_RATE_120HZ;
+        self.fps_cap = Some(MAX_RATE_120HZ);
+        self
+    }
+}
+
+impl<T: Decay> AnimationBuilder<T> {
+    /// momentum after a fling. the rest position comes from the velocity and friction, so
+    /// this replaces `animate_to`, `duration`, `easing` and `spring`
+    pub fn decay(mut self, config: DecayConfig) -> Self {
+        self.decay = Some(DecayTarget::new(config));
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_fps_caps_are_ignored() {
+        let builder = AnimationBuilder::<f64>::default().with_fps_cap(0);
+        assert_eq!(builder.fps_cap, None);
+        assert_eq!(builder.with_fps_cap(30).with_fps_cap(0).fps_cap, Some(30));
+    }
+
+    #[test]
+    fn zero_repeats_are_ignored() {
+        let builder = AnimationBuilder::<f64>::default().repeat(0);
+        assert_eq!(builder.iterations, Iterations::Count(1));
+        assert_eq!(builder.repeat(3).repeat(0).iterations, Iterations::Count(3));
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use super::AnimationId;
+
+/// how a queued animation left the queue
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimationOutcome {
+    /// played to the end, or had nothing to animate
+    Completed,
+    /// interrupted by `play_now`, or the player went away before it finished
+    Cancelled,
+    /// never started: removed from the queue by `drop_all` or `remove`, or turned away by
+    /// [`InterruptPolicy::IgnoreWhileBusy`](crate::controllers::InterruptPolicy::IgnoreWhileBusy)
+    Dropped,
+}
+
+#[derive(Default)]
+struct HandleState {
+    outcome: Option<AnimationOutcome>,
+    wakers: Vec<Waker>,
+}
+
+/// Resolves once the animation it was returned for leaves the queue. Clones resolve together,
+/// so several tasks can wait on the same animation.
+#[derive(Clone)]
+pub struct AnimationHandle {
+    id: AnimationId,
+    state: Rc<RefCell<HandleState>>,
+}
+
+impl AnimationHandle {
+    /// a handle and the completion that resolves it
+    pub(crate) fn pending(id: AnimationId) -> (Self, Completion) {
+        let state = Rc::new(RefCell::new(HandleState::default()));
+        (
+            Self {
+                id,
+                state: state.clone(),
+            },
+            Completion {
+                resolver: Rc::new(Resolver { state }),
+            },
+        )
+    }
+
+    pub fn id(&self) -> AnimationId {
+        self.id
+    }
+
+    /// `None` while the animation is queued or running
+    pub fn outcome(&self) -> Option<AnimationOutcome> {
+        self.state.borrow().outcome
+    }
+}
+
+impl Future for AnimationHandle {
+    type Output = AnimationOutcome;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        match state.outcome {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    state.wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl fmt::Debug for AnimationHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnimationHandle")
+            .field("id", &self.id)
+            .field("outcome", &self.outcome())
+            .finish()
+    }
+}
+
+/// The player's side of an [`AnimationHandle`]. Travels with the queued builder and is
+/// resolved exactly once, when the entry leaves the queue. Dropped unresolved, say along
+/// with its player, the handle resolves to [`AnimationOutcome::Cancelled`] so nothing
+/// awaits it forever.
+#[derive(Clone)]
+pub struct Completion {
+    /// shared by clones, only the last one dropped cancels
+    resolver: Rc<Resolver>,
+}
+
+impl Completion {
+    /// later outcomes are ignored, the first one sticks
+    pub fn resolve(&self, outcome: AnimationOutcome) {
+        self.resolver.resolve(outcome);
+    }
+}
+
+impl PartialEq for Completion {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.resolver, &other.resolver)
+    }
+}
+
+impl fmt::Debug for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Completion")
+            .field(&self.resolver.state.borrow().outcome)
+            .finish()
+    }
+}
+
+struct Resolver {
+    state: Rc<RefCell<HandleState>>,
+}
+
+impl Resolver {
+    fn resolve(&self, outcome: AnimationOutcome) {
+        let wakers = {
+            let mut state = self.state.borrow_mut();
+            if state.outcome.is_some() {
+                return;
+            }
+            state.outcome = Some(outcome);
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for Resolver {
+    fn drop(&mut self) {
+        self.resolve(AnimationOutcome::Cancelled);
+    }
+}
@@ -0,0 +1,900 @@
+use euclid::Rect;
+use web_time::Duration;
+
+use crate::{
+    clock::{MonotonicClock, SharedClock},
+    interpolate::Interpolate,
+    stopwatch::Stopwatch,
+};
+
+use super::{
+    AnimationBuilder, AnimationHandle, AnimationId, AnimationOutcome, AnimationQueue,
+    AnimationTransition, Channels, Completion, InterruptPolicy, Momentum, QueuedAnimation,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FlipbookStatus {
+    Busy,
+    Resting,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimationEventKind {
+    Started,
+    /// a transition advanced. delays don't produce frames
+    Frame,
+    Completed,
+    /// interrupted by `play_now` or dropped by `drop_all` after it started
+    Cancelled,
+}
+
+/// Lifecycle of a queued animation as it runs, see [`AnimationPlayer::record_events`]
+#[derive(Clone, PartialEq, Debug)]
+pub struct AnimationEvent<T: Interpolate = Rect<f64, f64>> {
+    pub kind: AnimationEventKind,
+    pub id: AnimationId,
+    /// the value when the event happened. `None` until a starting value is known
+    pub value: Option<T>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum ActiveAnimation<T: Interpolate> {
+    Transition(Box<AnimationTransition<T>>),
+    Delay(Duration),
+}
+
+/// Synchronous queue state machine behind a flipbook controller.
+/// Time only moves when [`AnimationPlayer::tick`] is called, so it can be stepped
+/// from a frame loop, a server, or a test with a [`crate::clock::VirtualClock`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct AnimationPlayer<T: Interpolate = Rect<f64, f64>> {
+    queue: AnimationQueue<T>,
+    active: Option<ActiveAnimation<T>>,
+    /// the running entry, and the completion resolving its handle
+    running: Option<(AnimationId, Completion)>,
+    stopwatch: Stopwatch,
+    value: Option<T>,
+    channels: Channels,
+    paused: bool,
+    /// motion cut off by `play_now`, handed to the next transition that starts
+    interrupted: Option<(AnimationTransition<T>, Duration)>,
+    /// its velocity at the cut, carried on by springs
+    momentum: Option<Momentum<T>>,
+    playback_rate: f64,
+    /// animation time and stopwatch reading when the rate last changed. time before that
+    /// keeps the rate it played at, so changing it never jumps
+    anchor: (Duration, Duration),
+    /// time into the running animation as of the last tick or seek
+    elapsed: Duration,
+    record_events: bool,
+    events: Vec<AnimationEvent<T>>,
+    /// for builders played without a policy of their own
+    interrupt_policy: InterruptPolicy,
+}
+
+impl<T: Interpolate> Default for AnimationPlayer<T> {
+    fn default() -> Self {
+        Self::new(MonotonicClock::shared())
+    }
+}
+
+impl<T: Interpolate> AnimationPlayer<T> {
+    pub fn new(clock: SharedClock) -> Self {
+        Self {
+            queue: AnimationQueue::new(),
+            active: None,
+            running: None,
+            stopwatch: Stopwatch::new(clock),
+            value: None,
+            channels: Channels::default(),
+            paused: false,
+            interrupted: None,
+            momentum: None,
+            playback_rate: 1.,
+            anchor: (Duration::ZERO, Duration::ZERO),
+            elapsed: Duration::ZERO,
+            record_events: false,
+            events: Vec::new(),
+            interrupt_policy: InterruptPolicy::default(),
+        }
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    pub fn channels(&self) -> &Channels {
+        &self.channels
+    }
+
+    pub fn status(&self) -> FlipbookStatus {
+        if self.active.is_some() || !self.queue.is_empty() {
+            FlipbookStatus::Busy
+        } else {
+            FlipbookStatus::Resting
+        }
+    }
+
+    /// iteration of the running transition, counting from 0. 0 while resting
+    pub fn iteration(&self) -> u32 {
+        match &self.active {
+            Some(ActiveAnimation::Transition(transition)) => transition.iteration(),
+            _ => 0,
+        }
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate
+    }
+
+    /// scales how fast animations and delays play, from the current frame on. `0.5` is half
+    /// speed, negative rates play backwards: animations start from their end, and one rewound
+    /// to its start ends there and the queue moves on, like the web animations api
+    pub fn set_playback_rate(&mut self, rate: f64) {
+        if !rate.is_finite() {
+            tracing::error!("ignoring playback rate {}", rate);
+            return;
+        }
+        self.anchor = (self.elapsed(), self.stopwatch.get_elapsed());
+        self.playback_rate = rate;
+    }
+
+    /// id of the running animation or delay
+    pub fn running_id(&self) -> Option<AnimationId> {
+        self.running.as_ref().map(|(id, _)| *id)
+    }
+
+    /// off by default, so nothing piles up when nobody drains them with
+    /// [`Self::take_events`]
+    pub fn record_events(&mut self, record: bool) {
+        self.record_events = record;
+        if !record {
+            self.events.clear();
+        }
+    }
+
+    /// events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<AnimationEvent<T>> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn emit(&mut self, kind: AnimationEventKind) {
+        if let (true, Some((id, _))) = (self.record_events, &self.running) {
+            self.events.push(AnimationEvent {
+                kind,
+                id: *id,
+                value: self.value.clone(),
+            });
+        }
+    }
+
+    /// linear progress of the running animation or delay, `None` while resting
+    pub fn progress(&self) -> Option<f64> {
+        match &self.active {
+            Some(ActiveAnimation::Transition(transition)) => Some(transition.progress()),
+            Some(ActiveAnimation::Delay(duration)) if duration.is_zero() => Some(1.),
+            Some(ActiveAnimation::Delay(duration)) => {
+                Some((self.elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.))
+            }
+            None => None,
+        }
+    }
+
+    /// jumps the running animation or delay to `progress`, `0.0` to `1.0`. see [`Self::seek_time`]
+    pub fn seek(&mut self, progress: f64) {
+        if progress.is_nan() {
+            tracing::error!("ignoring seek to progress {}", progress);
+            return;
+        }
+        let elapsed = match &self.active {
+            Some(ActiveAnimation::Transition(transition)) => transition.time_at_progress(progress),
+            Some(ActiveAnimation::Delay(duration)) => duration.mul_f64(progress.clamp(0., 1.)),
+            None => return,
+        };
+        self.jump_to(elapsed);
+    }
+
+    /// jumps the running animation or delay to `elapsed` into its forward run, so a reversed
+    /// animation lands where [`Self::seek`] would. the value updates right away, so scrubbing
+    /// works while paused. playing on from the end finishes on the next tick
+    pub fn seek_time(&mut self, elapsed: Duration) {
+        let elapsed = match &self.active {
+            Some(ActiveAnimation::Transition(transition)) => transition.time_at(elapsed),
+            Some(ActiveAnimation::Delay(_)) => elapsed,
+            None => return,
+        };
+        self.jump_to(elapsed);
+    }
+
+    /// continues the running animation from `elapsed` on its own clock
+    fn jump_to(&mut self, elapsed: Duration) {
+        if self.active.is_none() {
+            return;
+        }
+        self.anchor = (elapsed, self.stopwatch.get_elapsed());
+        self.elapsed = elapsed;
+        if let Some(ActiveAnimation::Transition(transition)) = self.active.as_mut() {
+            self.value = Some(transition.step(elapsed));
+            self.channels = transition.sample_channels(elapsed);
+            self.emit(AnimationEventKind::Frame);
+        }
+    }
+
+    /// plays the running animation backwards from where it is now, taking as long to get
+    /// back as it took to get here. a delay waits out the time it already waited again
+    pub fn reverse(&mut self) {
+        let elapsed = self.elapsed();
+        let reversed = match self.active.as_mut() {
+            Some(ActiveAnimation::Transition(transition)) => {
+                transition.step(elapsed);
+                transition.reverse()
+            }
+            Some(ActiveAnimation::Delay(duration)) => duration.saturating_sub(elapsed),
+            None => return,
+        };
+        self.jump_to(reversed);
+    }
+
+    /// time into the running animation, scaled by the playback rate
+    fn elapsed(&mut self) -> Duration {
+        let (anchor_elapsed, anchor_stopwatch) = self.anchor;
+        let since = self
+            .stopwatch
+            .get_elapsed()
+            .saturating_sub(anchor_stopwatch)
+            .as_secs_f64();
+        let elapsed = anchor_elapsed.as_secs_f64() + since * self.playback_rate;
+        Duration::try_from_secs_f64(elapsed.max(0.)).unwrap_or(Duration::MAX)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// fps cap of the running transition, if any
+    pub fn fps_cap(&self) -> Option<u64> {
+        match &self.active {
+            Some(ActiveAnimation::Transition(transition)) => transition.fps_cap,
+            _ => None,
+        }
+    }
+
+    /// whether calling [`AnimationPlayer::tick`] can make progress
+    pub fn needs_frames(&self) -> bool {
+        !self.paused && (self.active.is_some() || self.can_start_next())
+    }
+
+    /// ignored while an animation or delay is running
+    pub fn set_value(&mut self, value: T) {
+        if self.active.is_none() {
+            self.value = Some(value);
+        }
+    }
+
+    /// sets the value and channels at once, for animations driven from outside the queue.
+    /// that takes over: anything running or queued is cancelled, like with `play_now`
+    pub fn set_frame(&mut self, value: T, channels: Channels) {
+        if self.active.is_some() {
+            self.clear_active(AnimationOutcome::Cancelled);
+        }
+        self.queue.cancel_all();
+        self.interrupted = None;
+        self.momentum = None;
+        self.value = Some(value);
+        self.channels = channels;
+    }
+
+    /// the handle resolves when `anim` finishes or is cancelled or dropped before that
+    pub fn queue(&mut self, anim: AnimationBuilder<T>) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.queue_entry(entry);
+        handle
+    }
+
+    pub fn queue_entry(&mut self, entry: QueuedAnimation<T>) {
+        self.submit(entry, InterruptPolicy::Append);
+    }
+
+    pub fn interrupt_policy(&self) -> InterruptPolicy {
+        self.interrupt_policy
+    }
+
+    /// how `play` handles builders that don't set a policy
+    pub fn set_interrupt_policy(&mut self, policy: InterruptPolicy) {
+        self.interrupt_policy = policy;
+    }
+
+    /// starts or queues `anim` according to its interrupt policy, or the player's
+    pub fn play(&mut self, anim: AnimationBuilder<T>) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.play_entry(entry);
+        handle
+    }
+
+    pub fn play_entry(&mut self, entry: QueuedAnimation<T>) {
+        let policy = entry
+            .builder
+            .interrupt_policy
+            .unwrap_or(self.interrupt_policy);
+        self.submit(entry, policy);
+    }
+
+    fn submit(&mut self, mut entry: QueuedAnimation<T>, policy: InterruptPolicy) {
+        match policy {
+            InterruptPolicy::Append => self.queue.push(entry),
+            InterruptPolicy::IgnoreWhileBusy if self.status() == FlipbookStatus::Busy => {
+                tracing::info!("ignoring animation while busy");
+                entry.completion.resolve(AnimationOutcome::Dropped);
+            }
+            InterruptPolicy::IgnoreWhileBusy => {
+                self.queue.push(entry);
+                self.start_next();
+            }
+            InterruptPolicy::Replace => self.interrupt(entry, false),
+            InterruptPolicy::ReplaceAndKeepQueue => self.interrupt(entry, true),
+            InterruptPolicy::Blend(blend) => {
+                entry.builder.interrupt_blend = Some(blend);
+                self.interrupt(entry, false);
+            }
+        }
+    }
+
+    /// a running transition isn't dropped outright: the new one can blend out of it,
+    /// see [`AnimationBuilder::blend_interruptions`]
+    fn interrupt(&mut self, entry: QueuedAnimation<T>, keep_queue: bool) {
+        if let Some(ActiveAnimation::Transition(transition)) = self.active.take() {
+            let elapsed = self.elapsed();
+            self.momentum = Some(transition.momentum_at(elapsed));
+            self.interrupted = Some((*transition, elapsed));
+        }
+        self.clear_active(AnimationOutcome::Cancelled);
+        if keep_queue {
+            self.queue.insert(0, entry);
+        } else {
+            self.queue.play_now(entry);
+        }
+        self.start_next();
+        // only the transition started right away continues the interrupted motion
+        self.interrupted = None;
+        self.momentum = None;
+    }
+
+    /// entries waiting behind the running one, next first
+    pub fn queued(&self) -> impl Iterator<Item = &QueuedAnimation<T>> {
+        self.queue.iter()
+    }
+
+    /// queues `anim` at `index` among the waiting entries
+    pub fn insert(&mut self, index: usize, anim: AnimationBuilder<T>) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.insert_entry(index, entry);
+        handle
+    }
+
+    pub fn insert_entry(&mut self, index: usize, entry: QueuedAnimation<T>) {
+        self.queue.insert(index, entry);
+    }
+
+    /// takes a waiting entry out of the queue. the running animation is left alone,
+    /// interrupt that with `play_now` or `drop_all`
+    pub fn remove_queued(&mut self, id: AnimationId) -> bool {
+        self.queue.remove(id)
+    }
+
+    /// the waiting entry plays next, after the running one
+    pub fn move_to_front(&mut self, id: AnimationId) -> bool {
+        self.queue.move_to_front(id)
+    }
+
+    pub fn replace_queued(&mut self, id: AnimationId, anim: AnimationBuilder<T>) -> bool {
+        self.queue.replace(id, anim)
+    }
+
+    /// cancels everything running or queued. a running transition isn't dropped outright:
+    /// the new one can blend out of it, see [`AnimationBuilder::blend_interruptions`]
+    pub fn play_now(&mut self, anim: AnimationBuilder<T>) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.play_now_entry(entry);
+        handle
+    }
+
+    pub fn play_now_entry(&mut self, entry: QueuedAnimation<T>) {
+        self.submit(entry, InterruptPolicy::Replace);
+    }
+
+    /// only a running animation or delay can be paused
+    pub fn pause(&mut self) {
+        if self.active.is_some() {
+            self.paused = true;
+            // don't count pause duration as elapsed animation time
+            self.stopwatch.stop();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.stopwatch.start();
+        }
+    }
+
+    pub fn drop_all(&mut self) {
+        self.clear_active(AnimationOutcome::Dropped);
+        self.queue.drop_all();
+    }
+
+    /// advances the running animation to the clock's current time, starting the next
+    /// queued entry when it finishes. returns the current value.
+    pub fn tick(&mut self) -> Option<&T> {
+        if self.paused {
+            return self.value.as_ref();
+        }
+        if self.active.is_none() {
+            self.start_next();
+            return self.value.as_ref();
+        }
+
+        let elapsed = self.elapsed();
+        // backwards play ends at the start, once the playhead actually went back to it
+        let backwards = self.playback_rate < 0.;
+        let rewound = elapsed.is_zero() && !self.elapsed.is_zero();
+        self.elapsed = elapsed;
+        let (finished, frame) = match self.active.as_mut() {
+            Some(ActiveAnimation::Transition(transition)) => {
+                self.value = Some(transition.step(elapsed));
+                self.channels = transition.sample_channels(elapsed);
+                if transition.is_finished() {
+                    self.value = Some(transition.final_value().clone());
+                    self.channels = transition.final_channels().clone();
+                }
+                let ended = if backwards {
+                    rewound || transition.time_at_progress(1.).is_zero()
+                } else {
+                    transition.is_finished()
+                };
+                (ended, true)
+            }
+            Some(ActiveAnimation::Delay(duration)) => {
+                let ended = if backwards {
+                    rewound || duration.is_zero()
+                } else {
+                    elapsed >= *duration
+                };
+                (ended, false)
+            }
+            None => (false, false),
+        };
+        if frame {
+            self.emit(AnimationEventKind::Frame);
+        }
+
+        if finished {
+            self.clear_active(AnimationOutcome::Completed);
+            self.start_next();
+        }
+        self.value.as_ref()
+    }
+
+    fn clear_active(&mut self, outcome: AnimationOutcome) {
+        self.emit(match outcome {
+            AnimationOutcome::Completed => AnimationEventKind::Completed,
+            AnimationOutcome::Cancelled | AnimationOutcome::Dropped => {
+                AnimationEventKind::Cancelled
+            }
+        });
+        if let Some((_, completion)) = self.running.take() {
+            completion.resolve(outcome);
+        }
+        self.active = None;
+        self.paused = false;
+        self.stopwatch.clear();
+        self.anchor = (Duration::ZERO, Duration::ZERO);
+        self.elapsed = Duration::ZERO;
+    }
+
+    fn can_start_next(&self) -> bool {
+        self.queue
+            .peek_front()
+            .is_some_and(|anim| anim.is_delay() || anim.from.is_some() || self.value.is_some())
+    }
+
+    fn start_next(&mut self) {
+        tracing::info!("evaluating queue: {:?}", self.queue);
+
+        while self.can_start_next() {
+            let Some(QueuedAnimation {
+                id,
+                builder: anim_builder,
+                completion,
+            }) = self.queue.pop_front()
+            else {
+                return;
+            };
+            let active = if anim_builder.is_delay() {
+                ActiveAnimation::Delay(anim_builder.duration)
+            } else {
+                // can_start_next guarantees a known origin
+                let Some(mut transition) = AnimationTransition::resolve_endpoints(
+                    anim_builder,
+                    self.value.as_ref(),
+                    &self.channels,
+                ) else {
+                    completion.resolve(AnimationOutcome::Completed);
+                    continue;
+                };
+                if let Some(momentum) = self.momentum.take() {
+                    transition = transition.carrying(momentum);
+                }
+                if transition.is_still() {
+                    tracing::error!(
+                        "requested animation has same origin and destination: {:?} {:?}",
+                        transition.from,
+                        transition.to
+                    );
+                    completion.resolve(AnimationOutcome::Completed);
+                    continue;
+                }
+                self.value = Some(transition.from.clone());
+                self.channels = transition.channels_from.clone();
+                if let Some((interrupted, elapsed)) = self.interrupted.take() {
+                    transition = transition.interrupting(interrupted, elapsed);
+                }
+                ActiveAnimation::Transition(Box::new(transition))
+            };
+            let end = match &active {
+                ActiveAnimation::Transition(transition) => transition.time_at_progress(1.),
+                ActiveAnimation::Delay(duration) => *duration,
+            };
+            self.active = Some(active);
+            self.running = Some((id, completion));
+            self.stopwatch.start();
+            self.emit(AnimationEventKind::Started);
+            if self.playback_rate < 0. {
+                // playing backwards starts at the end
+                self.jump_to(end);
+            }
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use euclid::{Point2D, Size2D};
+
+    use crate::{clock::VirtualClock, controllers::SpringConfig, easing::Easing};
+
+    use super::*;
+
+    fn rect(x: f64, y: f64) -> Rect<f64, f64> {
+        Rect::new(Point2D::new(x, y), Size2D::new(10., 10.))
+    }
+
+    fn linear(to: Rect<f64, f64>) -> AnimationBuilder {
+        AnimationBuilder::default()
+            .animate_to(to)
+            .with_duration(Duration::from_millis(1000))
+            .with_easing(Easing::Linear)
+    }
+
+    /// easing runs in f32
+    fn assert_x(value: Option<&Rect<f64, f64>>, x: f64) {
+        let actual = value.expect("no value").origin.x;
+        assert!((actual - x).abs() < 1e-4, "{actual} != {x}");
+    }
+
+    /// a player resting at `at`, driven by the returned clock
+    fn player_at(at: Rect<f64, f64>) -> (AnimationPlayer, VirtualClock) {
+        let clock = VirtualClock::new();
+        let mut player = AnimationPlayer::new(clock.shared());
+        player.set_value(at);
+        (player, clock)
+    }
+
+    #[test]
+    fn linear_tween_steps_with_the_clock() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(100., 200.)));
+
+        assert_eq!(player.tick(), Some(&rect(0., 0.)));
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(player.tick(), Some(&rect(25., 50.)));
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(75., 150.)));
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(player.tick(), Some(&rect(100., 200.)));
+        assert_eq!(player.status(), FlipbookStatus::Resting);
+    }
+
+    #[test]
+    fn delay_holds_the_value_until_it_ends() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(AnimationBuilder::new_delay(Duration::from_millis(500)));
+        player.queue(linear(rect(100., 0.)));
+
+        player.tick();
+        clock.advance(Duration::from_millis(499));
+        assert_eq!(player.tick(), Some(&rect(0., 0.)));
+        assert_eq!(player.status(), FlipbookStatus::Busy);
+
+        // the tween starts on the tick that ends the delay
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(player.tick(), Some(&rect(0., 0.)));
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(50., 0.)));
+    }
+
+    #[test]
+    fn negative_rates_play_queued_animations_from_their_end() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.set_playback_rate(-1.);
+        player.queue(linear(rect(100., 0.)));
+        player.queue(linear(rect(200., 0.)));
+
+        assert_eq!(player.tick(), Some(&rect(100., 0.)));
+        clock.advance(Duration::from_millis(16));
+        let x = player.tick().unwrap().origin.x;
+        assert!((x - 98.4).abs() < 1e-4, "{x}");
+        assert_eq!(player.status(), FlipbookStatus::Busy);
+
+        // back at the start, the next one starts from its end
+        clock.advance(Duration::from_millis(1000));
+        assert_eq!(player.tick(), Some(&rect(200., 0.)));
+        assert_eq!(player.status(), FlipbookStatus::Busy);
+    }
+
+    #[test]
+    fn seeking_to_nan_is_ignored() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(100., 0.)));
+        player.queue(AnimationBuilder::new_delay(Duration::from_millis(500)));
+        player.tick();
+        clock.advance(Duration::from_millis(250));
+        player.tick();
+
+        player.seek(f64::NAN);
+        assert_eq!(player.value(), Some(&rect(25., 0.)));
+        // out of range still clamps
+        player.seek(f64::INFINITY);
+        assert_eq!(player.value(), Some(&rect(100., 0.)));
+
+        player.tick();
+        assert!(player.progress().is_some_and(|progress| progress < 1.));
+        player.seek(f64::NAN);
+        player.seek(f64::NEG_INFINITY);
+        assert_eq!(player.progress(), Some(0.));
+    }
+
+    #[test]
+    fn dropping_the_player_cancels_pending_handles() {
+        let (mut player, _clock) = player_at(rect(0., 0.));
+        let running = player.queue(linear(rect(100., 0.)));
+        let queued = player.queue(linear(rect(200., 0.)));
+        player.tick();
+        assert_eq!(running.outcome(), None);
+
+        drop(player);
+        assert_eq!(running.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(queued.outcome(), Some(AnimationOutcome::Cancelled));
+    }
+
+    #[test]
+    fn set_frame_cancels_what_was_playing() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        let running = player.queue(linear(rect(100., 0.)));
+        let queued = player.queue(linear(rect(200., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(500));
+        player.tick();
+
+        player.set_frame(rect(7., 7.), Channels::default());
+        assert_eq!(running.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(queued.outcome(), Some(AnimationOutcome::Cancelled));
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(player.tick(), Some(&rect(7., 7.)));
+        assert_eq!(player.status(), FlipbookStatus::Resting);
+    }
+
+    #[test]
+    fn springs_carry_the_interrupted_velocity() {
+        let step = Duration::from_millis(1);
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(1000., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(499));
+        let before = player.tick().unwrap().origin.x;
+        clock.advance(step);
+        let at = player.tick().unwrap().origin.x;
+
+        player.play_now(
+            AnimationBuilder::default()
+                .animate_to(rect(0., 0.))
+                .spring(SpringConfig::default()),
+        );
+        assert_eq!(player.tick().unwrap().origin.x, at);
+        clock.advance(step);
+        let after = player.tick().unwrap().origin.x;
+        // still heading right at about a pixel per millisecond, not reversing from rest
+        assert!(
+            ((after - at) - (at - before)).abs() < 0.1,
+            "{before} {at} {after}"
+        );
+    }
+
+    #[test]
+    fn springs_retargeted_in_place_keep_moving() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(1000., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(500));
+        let at = player.tick().unwrap().origin.x;
+
+        player.play_now(
+            AnimationBuilder::default()
+                .animate_to(rect(at, 0.))
+                .spring(SpringConfig::default()),
+        );
+        player.tick();
+        clock.advance(Duration::from_millis(16));
+        assert!(player.tick().unwrap().origin.x > at);
+        assert_eq!(player.status(), FlipbookStatus::Busy);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(player.tick(), Some(&rect(at, 0.)));
+        assert_eq!(player.status(), FlipbookStatus::Resting);
+    }
+
+    #[test]
+    fn play_now_starts_from_the_interrupted_value() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        let first = player.queue(linear(rect(100., 0.)));
+        let queued = player.queue(linear(rect(0., 100.)));
+
+        player.tick();
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(50., 0.)));
+
+        player.play_now(linear(rect(50., 100.)));
+        assert_eq!(player.value(), Some(&rect(50., 0.)));
+        assert_eq!(first.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(queued.outcome(), Some(AnimationOutcome::Cancelled));
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(50., 50.)));
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(50., 100.)));
+        assert_eq!(player.status(), FlipbookStatus::Resting);
+    }
+
+    #[test]
+    fn repeats_count_iterations_then_move_on() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        let repeated = player.queue(linear(rect(100., 0.)).repeat(2));
+        player.queue(linear(rect(100., 100.)));
+
+        player.tick();
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(50., 0.)));
+        assert_eq!(player.iteration(), 0);
+        // the second pass starts over from where the first started
+        clock.advance(Duration::from_millis(1000));
+        assert_eq!(player.tick(), Some(&rect(50., 0.)));
+        assert_eq!(player.iteration(), 1);
+        assert_eq!(repeated.outcome(), None);
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(100., 0.)));
+        assert_eq!(repeated.outcome(), Some(AnimationOutcome::Completed));
+        assert_eq!(player.iteration(), 0);
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(player.tick(), Some(&rect(100., 50.)));
+    }
+
+    #[test]
+    fn playback_rate_scales_from_the_current_frame() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(100., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(200));
+        assert_x(player.tick(), 20.);
+
+        player.set_playback_rate(0.5);
+        clock.advance(Duration::from_millis(200));
+        assert_x(player.tick(), 30.);
+        player.set_playback_rate(f64::NAN);
+        assert_eq!(player.playback_rate(), 0.5);
+    }
+
+    #[test]
+    fn seek_jumps_to_progress_even_while_paused() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(100., 0.)));
+        player.tick();
+        player.pause();
+        player.seek(0.75);
+        assert_x(player.value(), 75.);
+        assert_eq!(player.progress(), Some(0.75));
+
+        clock.advance(Duration::from_millis(100));
+        assert_x(player.tick(), 75.);
+        player.resume();
+        clock.advance(Duration::from_millis(100));
+        assert_x(player.tick(), 85.);
+    }
+
+    #[test]
+    fn seek_time_after_reverse_lands_where_seek_does() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.queue(linear(rect(100., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(500));
+        player.tick();
+        player.reverse();
+
+        player.seek(0.25);
+        assert_x(player.value(), 25.);
+        player.seek_time(Duration::from_millis(250));
+        assert_x(player.value(), 25.);
+        // and keeps heading back from there
+        clock.advance(Duration::from_millis(100));
+        assert_x(player.tick(), 15.);
+    }
+
+    #[test]
+    fn events_follow_the_lifecycle() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        player.record_events(true);
+        let first = player.queue(linear(rect(100., 0.)));
+        player.tick();
+        clock.advance(Duration::from_millis(1000));
+        player.tick();
+        player.queue(linear(rect(0., 0.)));
+        player.tick();
+        player.drop_all();
+
+        let events: Vec<_> = player
+            .take_events()
+            .into_iter()
+            .map(|event| (event.id == first.id(), event.kind))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (true, AnimationEventKind::Started),
+                (true, AnimationEventKind::Frame),
+                (true, AnimationEventKind::Completed),
+                (false, AnimationEventKind::Started),
+                (false, AnimationEventKind::Cancelled),
+            ]
+        );
+        assert!(player.take_events().is_empty());
+    }
+
+    #[test]
+    fn interrupt_policies() {
+        let (mut player, clock) = player_at(rect(0., 0.));
+        let running = player.play(linear(rect(100., 0.)));
+        player.tick();
+
+        player.set_interrupt_policy(InterruptPolicy::IgnoreWhileBusy);
+        let ignored = player.play(linear(rect(0., 100.)));
+        assert_eq!(ignored.outcome(), Some(AnimationOutcome::Dropped));
+
+        let queued =
+            player.play(linear(rect(50., 0.)).with_interrupt_policy(InterruptPolicy::Append));
+        let kept = player.play(
+            linear(rect(0., 50.)).with_interrupt_policy(InterruptPolicy::ReplaceAndKeepQueue),
+        );
+        assert_eq!(running.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(player.running_id(), Some(kept.id()));
+        assert_eq!(queued.outcome(), None);
+
+        clock.advance(Duration::from_millis(1000));
+        player.tick();
+        assert_eq!(kept.outcome(), Some(AnimationOutcome::Completed));
+        assert_eq!(player.running_id(), Some(queued.id()));
+
+        player.play(linear(rect(9., 9.)).with_interrupt_policy(InterruptPolicy::Replace));
+        assert_eq!(queued.outcome(), Some(AnimationOutcome::Cancelled));
+    }
+}
@@ -1,14 +1,62 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
-use super::AnimationBuilder;
+use euclid::Rect;
 
+use crate::interpolate::Interpolate;
+
+use super::{AnimationBuilder, AnimationHandle, AnimationOutcome, Completion};
+
+static NEXT_ANIMATION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a queued animation across the queue, its handle and its lifecycle events.
+/// Unique for the lifetime of the program.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct AnimationId(u64);
+
+impl AnimationId {
+    pub fn next() -> Self {
+        Self(NEXT_ANIMATION_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// a builder waiting its turn, with the completion that resolves its handle
 #[derive(Clone, PartialEq, Debug)]
+pub struct QueuedAnimation<T: Interpolate = Rect<f64, f64>> {
+    pub id: AnimationId,
+    pub builder: AnimationBuilder<T>,
+    pub completion: Completion,
+}
+
+impl<T: Interpolate> QueuedAnimation<T> {
+    pub fn new(builder: AnimationBuilder<T>) -> (Self, AnimationHandle) {
+        let id = AnimationId::next();
+        let (handle, completion) = AnimationHandle::pending(id);
+        (
+            Self {
+                id,
+                builder,
+                completion,
+            },
+            handle,
+        )
+    }
+}
 
-pub struct AnimationQueue {
-    queue: VecDeque<AnimationBuilder>,
+#[derive(Clone, PartialEq, Debug)]
+pub struct AnimationQueue<T: Interpolate = Rect<f64, f64>> {
+    queue: VecDeque<QueuedAnimation<T>>,
 }
 
-impl AnimationQueue {
+impl<T: Interpolate> Default for AnimationQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Interpolate> AnimationQueue<T> {
     pub fn new() -> Self {
         Self {
             queue: VecDeque::new(),
@@ -16,24 +64,79 @@ impl AnimationQueue {
     }
 
     pub fn drop_all(&mut self) {
-        self.queue.clear();
+        self.clear(AnimationOutcome::Dropped);
+    }
+
+    /// every entry waiting resolves as cancelled
+    pub fn cancel_all(&mut self) {
+        self.clear(AnimationOutcome::Cancelled);
     }
 
-    pub fn play_now(&mut self, anim: AnimationBuilder) {
+    /// the entries waiting are cancelled in favour of `anim`
+    pub fn play_now(&mut self, anim: QueuedAnimation<T>) {
         tracing::info!("play now from queue");
-        self.drop_all();
+        self.clear(AnimationOutcome::Cancelled);
         self.push(anim);
     }
 
-    pub fn push(&mut self, anim: AnimationBuilder) {
+    fn clear(&mut self, outcome: AnimationOutcome) {
+        for entry in self.queue.drain(..) {
+            entry.completion.resolve(outcome);
+        }
+    }
+
+    pub fn push(&mut self, anim: QueuedAnimation<T>) {
         self.queue.push_back(anim);
     }
 
+    /// past the end appends
+    pub fn insert(&mut self, index: usize, anim: QueuedAnimation<T>) {
+        self.queue.insert(index.min(self.queue.len()), anim);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QueuedAnimation<T>> {
+        self.queue.iter()
+    }
+
+    fn position(&self, id: AnimationId) -> Option<usize> {
+        self.queue.iter().position(|entry| entry.id == id)
+    }
+
+    /// its handle resolves to [`AnimationOutcome::Dropped`]. false if `id` isn't queued
+    pub fn remove(&mut self, id: AnimationId) -> bool {
+        let Some(entry) = self.position(id).and_then(|index| self.queue.remove(index)) else {
+            return false;
+        };
+        entry.completion.resolve(AnimationOutcome::Dropped);
+        true
+    }
+
+    pub fn move_to_front(&mut self, id: AnimationId) -> bool {
+        let Some(entry) = self.position(id).and_then(|index| self.queue.remove(index)) else {
+            return false;
+        };
+        self.queue.push_front(entry);
+        true
+    }
+
+    /// swaps the builder in place. the entry keeps its id and its handle
+    pub fn replace(&mut self, id: AnimationId, builder: AnimationBuilder<T>) -> bool {
+        let Some(entry) = self.queue.iter_mut().find(|entry| entry.id == id) else {
+            return false;
+        };
+        entry.builder = builder;
+        true
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty()
     }
 
-    pub fn pop_front(&mut self) -> Option<AnimationBuilder> {
+    pub fn peek_front(&self) -> Option<&AnimationBuilder<T>> {
+        self.queue.front().map(|entry| &entry.builder)
+    }
+
+    pub fn pop_front(&mut self) -> Option<QueuedAnimation<T>> {
         self.queue.pop_front()
     }
 
@@ -41,3 +144,63 @@ impl AnimationQueue {
         self.queue.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use web_time::Duration;
+
+    use super::*;
+
+    fn entry(millis: u64) -> (QueuedAnimation<f64>, AnimationHandle) {
+        QueuedAnimation::new(AnimationBuilder::new_delay(Duration::from_millis(millis)))
+    }
+
+    fn durations(queue: &AnimationQueue<f64>) -> Vec<u128> {
+        queue
+            .iter()
+            .map(|entry| entry.builder.duration.as_millis())
+            .collect()
+    }
+
+    #[test]
+    fn edits_keep_ids_and_resolve_removed_handles() {
+        let mut queue = AnimationQueue::new();
+        let (a, _) = entry(1);
+        let (b, b_handle) = entry(2);
+        let (c, c_handle) = entry(3);
+        let (b_id, c_id) = (b.id, c.id);
+        queue.push(a);
+        queue.push(b);
+        queue.push(c);
+
+        assert!(queue.move_to_front(c_id));
+        assert_eq!(durations(&queue), [3, 1, 2]);
+        assert!(queue.replace(c_id, AnimationBuilder::new_delay(Duration::from_millis(4))));
+        assert_eq!(durations(&queue), [4, 1, 2]);
+        assert_eq!(c_handle.outcome(), None);
+
+        assert!(queue.remove(b_id));
+        assert!(!queue.remove(b_id));
+        assert_eq!(b_handle.outcome(), Some(AnimationOutcome::Dropped));
+        assert_eq!(durations(&queue), [4, 1]);
+
+        let (d, _) = entry(5);
+        queue.insert(10, d);
+        assert_eq!(durations(&queue), [4, 1, 5]);
+    }
+
+    #[test]
+    fn play_now_cancels_and_drop_all_drops() {
+        let mut queue = AnimationQueue::new();
+        let (a, a_handle) = entry(1);
+        let (b, b_handle) = entry(2);
+        queue.push(a);
+        queue.play_now(b);
+        assert_eq!(a_handle.outcome(), Some(AnimationOutcome::Cancelled));
+        assert_eq!(durations(&queue), [2]);
+
+        queue.drop_all();
+        assert_eq!(b_handle.outcome(), Some(AnimationOutcome::Dropped));
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    color::{Color, ColorSpace},
+    css::{is_css_property_name, CssLength},
+    interpolate::Interpolate,
+};
+
+pub const DEFAULT_OPACITY: f64 = 1.0;
+pub const DEFAULT_ROTATE: f64 = 0.0;
+pub const DEFAULT_SCALE: (f64, f64) = (1.0, 1.0);
+pub const DEFAULT_SKEW: (f64, f64) = (0.0, 0.0);
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::TRANSPARENT;
+pub const DEFAULT_BORDER_COLOR: Color = Color::BLACK;
+pub const DEFAULT_TEXT_COLOR: Color = Color::BLACK;
+
+/// Style values animated alongside the main value with the same easing and timing.
+/// A channel stays `None` until an animation sets it, and unset channels aren't rendered.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Channels {
+    pub opacity: Option<f64>,
+    /// degrees
+    pub rotate: Option<f64>,
+    /// x and y factors
+    pub scale: Option<(f64, f64)>,
+    /// x and y angles in degrees
+    pub skew: Option<(f64, f64)>,
+    pub background_color: Option<Color>,
+    pub border_color: Option<Color>,
+    pub text_color: Option<Color>,
+    /// space the color channels blend in. not rendered
+    pub color_space: ColorSpace,
+    /// arbitrary numeric css properties by name, e.g. `padding` or `font-size`
+    pub properties: BTreeMap<String, CssLength>,
+}
+
+impl Channels {
+    pub fn to_css(&self) -> String {
+        self.to_css_after_transform(None)
+    }
+
+    /// like [`Self::to_css`], with `transform` applied ahead of the transform channels
+    pub fn to_css_after_transform(&self, transform: Option<String>) -> String {
+        let mut css = String::new();
+        if let Some(opacity) = self.opacity {
+            css.push_str(&format!("opacity: {};", opacity.clamp(0., 1.)));
+        }
+        let transform = match (transform, self.transform_css()) {
+            (Some(before), Some(channels)) => Some(format!("{} {}", before, channels)),
+            (before, channels) => before.or(channels),
+        };
+        if let Some(transform) = transform {
+            css.push_str(&format!("transform: {};", transform));
+        }
+        if let Some(color) = self.background_color {
+            css.push_str(&format!("background-color: {};", color.to_css()));
+        }
+        if let Some(color) = self.border_color {
+            css.push_str(&format!("border-color: {};", color.to_css()));
+        }
+        if let Some(color) = self.text_color {
+            css.push_str(&format!("color: {};", color.to_css()));
+        }
+        // the map is public, so names are checked again where they reach the style
+        for (name, length) in self
+            .properties
+            .iter()
+            .filter(|(name, _)| is_css_property_name(name))
+        {
+            css.push_str(&format!("{}: {};", name, length.to_css()));
+        }
+        css
+    }
+
+    /// `None` when no transform channel has been animated
+    pub fn transform_css(&self) -> Option<String> {
+        let mut functions = Vec::new();
+        if let Some(rotate) = self.rotate {
+            functions.push(format!("rotate({}deg)", rotate));
+        }
+        if let Some((x, y)) = self.scale {
+            functions.push(format!("scale({}, {})", x, y));
+        }
+        if let Some((x, y)) = self.skew {
+            functions.push(format!("skew({}deg, {}deg)", x, y));
+        }
+        (!functions.is_empty()).then(|| functions.join(" "))
+    }
+}
+
+impl Interpolate for Channels {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        Self {
+            opacity: interpolate_channel(&self.opacity, &to.opacity, t),
+            rotate: interpolate_channel(&self.rotate, &to.rotate, t),
+            scale: interpolate_channel(&self.scale, &to.scale, t),
+            skew: interpolate_channel(&self.skew, &to.skew, t),
+            background_color: interpolate_color(
+                &self.background_color,
+                &to.background_color,
+                t,
+                to.color_space,
+            ),
+            border_color: interpolate_color(
+                &self.border_color,
+                &to.border_color,
+                t,
+                to.color_space,
+            ),
+            text_color: interpolate_color(&self.text_color, &to.text_color, t, to.color_space),
+            color_space: to.color_space,
+            properties: to
+                .properties
+                .iter()
+                .map(|(name, to_length)| {
+                    let length = self
+                        .properties
+                        .get(name)
+                        .map_or(*to_length, |from| from.interpolate(to_length, t));
+                    (name.clone(), length)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn interpolate_channel<V: Interpolate>(from: &Option<V>, to: &Option<V>, t: f64) -> Option<V> {
+    match (from, to) {
+        (Some(from), Some(to)) => Some(from.interpolate(to, t)),
+        _ => to.clone(),
+    }
+}
+
+fn interpolate_color(
+    from: &Option<Color>,
+    to: &Option<Color>,
+    t: f64,
+    space: ColorSpace,
+) -> Option<Color> {
+    match (from, to) {
+        (Some(from), Some(to)) => Some(from.interpolate_in(to, t, space)),
+        _ => *to,
+    }
+}
+
+/// A channel endpoint requested by a builder. A missing `from` starts at the current value.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChannelTarget<V> {
+    pub from: Option<V>,
+    pub to: Option<V>,
+}
+
+impl<V> Default for ChannelTarget<V> {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+        }
+    }
+}
+
+impl<V: Clone> ChannelTarget<V> {
+    pub fn is_set(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    /// any endpoint that isn't given falls back to the current value, then to `default`
+    fn resolve(&self, current: &Option<V>, default: V) -> (Option<V>, Option<V>) {
+        if !self.is_set() {
+            return (current.clone(), current.clone());
+        }
+        let fallback = || current.clone().unwrap_or_else(|| default.clone());
+        (
+            Some(self.from.clone().unwrap_or_else(fallback)),
+            Some(self.to.clone().unwrap_or_else(fallback)),
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ChannelTargets {
+    pub opacity: ChannelTarget<f64>,
+    pub rotate: ChannelTarget<f64>,
+    pub scale: ChannelTarget<(f64, f64)>,
+    pub skew: ChannelTarget<(f64, f64)>,
+    pub background_color: ChannelTarget<Color>,
+    pub border_color: ChannelTarget<Color>,
+    pub text_color: ChannelTarget<Color>,
+    pub color_space: ColorSpace,
+    pub properties: BTreeMap<String, ChannelTarget<CssLength>>,
+}
+
+impl ChannelTargets {
+    pub fn is_set(&self) -> bool {
+        self.opacity.is_set()
+            || self.rotate.is_set()
+            || self.scale.is_set()
+            || self.skew.is_set()
+            || self.background_color.is_set()
+            || self.border_color.is_set()
+            || self.text_color.is_set()
+            || self.properties.values().any(ChannelTarget::is_set)
+    }
+
+    /// start and end channel values for a transition beginning at `current`
+    pub fn resolve(&self, current: &Channels) -> (Channels, Channels) {
+        let (opacity_from, opacity_to) = self.opacity.resolve(&current.opacity, DEFAULT_OPACITY);
+        let (rotate_from, rotate_to) = self.rotate.resolve(&current.rotate, DEFAULT_ROTATE);
+        let (scale_from, scale_to) = self.scale.resolve(&current.scale, DEFAULT_SCALE);
+        let (skew_from, skew_to) = self.skew.resolve(&current.skew, DEFAULT_SKEW);
+        let (background_from, background_to) = self
+            .background_color
+            .resolve(&current.background_color, DEFAULT_BACKGROUND_COLOR);
+        let (border_from, border_to) = self
+            .border_color
+            .resolve(&current.border_color, DEFAULT_BORDER_COLOR);
+        let (text_from, text_to) = self
+            .text_color
+            .resolve(&current.text_color, DEFAULT_TEXT_COLOR);
+        // properties this builder doesn't touch carry over unchanged
+        let mut properties_from = current.properties.clone();
+        let mut properties_to = current.properties.clone();
+        for (name, target) in &self.properties {
+            // an unknown starting value is zero in the target's unit
+            let default = target
+                .to
+                .or(target.from)
+                .map_or(CssLength::px(0.), |length| CssLength::new(0., length.unit));
+            let current_length = current.properties.get(name).copied();
+            if let (Some(from), Some(to)) = target.resolve(&current_length, default) {
+                properties_from.insert(name.clone(), from);
+                properties_to.insert(name.clone(), to);
+            }
+        }
+        (
+            Channels {
+                opacity: opacity_from,
+                rotate: rotate_from,
+                scale: scale_from,
+                skew: skew_from,
+                background_color: background_from,
+                border_color: border_from,
+                text_color: text_from,
+                color_space: self.color_space,
+                properties: properties_from,
+            },
+            Channels {
+                opacity: opacity_to,
+                rotate: rotate_to,
+                scale: scale_to,
+                skew: skew_to,
+                background_color: background_to,
+                border_color: border_to,
+                text_color: text_to,
+                color_space: self.color_space,
+                properties: properties_to,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controllers::AnimationBuilder;
+
+    use super::*;
+
+    #[test]
+    fn only_animated_channels_are_rendered() {
+        assert_eq!(Channels::default().to_css(), "");
+        let channels = Channels {
+            opacity: Some(0.25),
+            ..Channels::default()
+        };
+        assert_eq!(channels.to_css(), "opacity: 0.25;");
+    }
+
+    #[test]
+    fn opacity_is_clamped_when_easing_overshoots() {
+        let from = Channels {
+            opacity: Some(0.),
+            ..Channels::default()
+        };
+        let to = Channels {
+            opacity: Some(1.),
+            ..Channels::default()
+        };
+        // BackOut overshoots past 1, BackIn dips below 0
+        assert_eq!(from.interpolate(&to, 1.2).to_css(), "opacity: 1;");
+        assert_eq!(from.interpolate(&to, -0.2).to_css(), "opacity: 0;");
+    }
+
+    #[test]
+    fn transform_functions_keep_their_order() {
+        let channels = Channels {
+            skew: Some((10., 0.)),
+            scale: Some((2., 0.5)),
+            rotate: Some(45.),
+            opacity: Some(0.5),
+            background_color: Some(Color::BLACK),
+            ..Channels::default()
+        };
+        assert_eq!(
+            channels.transform_css().as_deref(),
+            Some("rotate(45deg) scale(2, 0.5) skew(10deg, 0deg)")
+        );
+        assert_eq!(
+            channels.to_css(),
+            format!(
+                "opacity: 0.5;transform: rotate(45deg) scale(2, 0.5) skew(10deg, 0deg);background-color: {};",
+                Color::BLACK.to_css()
+            )
+        );
+        assert_eq!(Channels::default().transform_css(), None);
+    }
+
+    #[test]
+    fn invalid_property_names_never_reach_the_style() {
+        let builder: AnimationBuilder = AnimationBuilder::default()
+            .animate_property_to("padding", CssLength::px(4.))
+            .animate_property_to("top: 0; color", CssLength::px(1.));
+        assert_eq!(builder.channels.properties.len(), 1);
+
+        let mut channels = Channels::default();
+        channels
+            .properties
+            .insert("width;position:fixed".into(), CssLength::px(1.));
+        channels
+            .properties
+            .insert("padding".into(), CssLength::px(4.));
+        assert_eq!(channels.to_css(), "padding: 4px;");
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+
+use euclid::{Point2D, Rect, Vector2D};
+use web_time::Duration;
+
+use crate::interpolate::Interpolate;
+
+/// a fling at 1000px/s coasts about 330px
+pub const DEFAULT_DECAY_FRICTION: f64 = 3.;
+pub const DEFAULT_DECAY_REST_VELOCITY: f64 = 10.;
+/// lower rest velocities are raised to this. the decay never gets to 0, so a fling
+/// resting at 0 would coast forever
+pub const MIN_DECAY_REST_VELOCITY: f64 = 0.01;
+
+/// Momentum after a drag or swipe. The value keeps moving at `velocity` and slows down
+/// exponentially; where it stops is worked out from the physics.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DecayConfig {
+    /// px per second at release
+    pub velocity: Vector2D<f64, f64>,
+    /// how quickly the velocity dies off, per second. the total distance is `velocity / friction`.
+    /// flings without a positive friction stay where they are
+    pub friction: f64,
+    /// stops once slower than this, in px per second. at least [`MIN_DECAY_REST_VELOCITY`]
+    pub rest_velocity: f64,
+    /// the fling slows down to stop at the edge instead of leaving this area
+    pub bounds: Option<Rect<f64, f64>>,
+}
+
+impl DecayConfig {
+    pub fn new(velocity: Vector2D<f64, f64>) -> Self {
+        Self {
+            velocity,
+            friction: DEFAULT_DECAY_FRICTION,
+            rest_velocity: DEFAULT_DECAY_REST_VELOCITY,
+            bounds: None,
+        }
+    }
+
+    pub fn with_friction(mut self, friction: f64) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_rest_velocity(mut self, rest_velocity: f64) -> Self {
+        self.rest_velocity = rest_velocity;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Rect<f64, f64>) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// where `from` comes to rest, and the decay it gets there with. flings already at rest
+    /// stay where they are
+    fn resolve<T: Decay>(&self, from: &T) -> (T, DecayCurve) {
+        let friction = self.friction;
+        let rest_velocity = self.rest_velocity.max(MIN_DECAY_REST_VELOCITY);
+        let at_rest = (from.clone(), DecayCurve::new(friction, 0., rest_velocity));
+        if !self.velocity.x.is_finite() || !self.velocity.y.is_finite() {
+            tracing::error!("ignoring fling with velocity {:?}", self.velocity);
+            return at_rest;
+        }
+        if !friction.is_finite() || friction <= 0. {
+            tracing::error!("ignoring fling with friction {}", friction);
+            return at_rest;
+        }
+        let (to, travelled) = from.translate_within(self.velocity / friction, self.bounds.as_ref());
+        // a fling cut short by the bounds was a slower one
+        let speed = self.velocity.length() * travelled.clamp(0., 1.);
+        if speed <= rest_velocity {
+            return at_rest;
+        }
+        (to, DecayCurve::new(friction, speed, rest_velocity))
+    }
+}
+
+/// Values that can be flung. Only positions have a velocity to decay, so this is implemented
+/// for points and rects rather than everything that interpolates.
+pub trait Decay: Interpolate {
+    /// moves by `offset`, but no further than keeps it inside `bounds`. returns the moved value
+    /// and the share of `offset` travelled. the direction is kept when the offset is cut short
+    fn translate_within(
+        &self,
+        offset: Vector2D<f64, f64>,
+        bounds: Option<&Rect<f64, f64>>,
+    ) -> (Self, f64);
+}
+
+impl Decay for Point2D<f64, f64> {
+    fn translate_within(
+        &self,
+        offset: Vector2D<f64, f64>,
+        bounds: Option<&Rect<f64, f64>>,
+    ) -> (Self, f64) {
+        let travelled = bounds.map_or(1., |bounds| travel_within(self, self, offset, bounds));
+        (*self + offset * travelled, travelled)
+    }
+}
+
+impl Decay for Rect<f64, f64> {
+    fn translate_within(
+        &self,
+        offset: Vector2D<f64, f64>,
+        bounds: Option<&Rect<f64, f64>>,
+    ) -> (Self, f64) {
+        let travelled = bounds.map_or(1., |bounds| {
+            travel_within(&self.min(), &self.max(), offset, bounds)
+        });
+        (self.translate(offset * travelled), travelled)
+    }
+}
+
+/// the largest share of `offset` that keeps the box `min..max` inside `bounds`
+fn travel_within(
+    min: &Point2D<f64, f64>,
+    max: &Point2D<f64, f64>,
+    offset: Vector2D<f64, f64>,
+    bounds: &Rect<f64, f64>,
+) -> f64 {
+    let axis = |offset: f64, room_before: f64, room_after: f64| {
+        let room = if offset > 0. { room_after } else { room_before };
+        if offset == 0. {
+            1.
+        } else {
+            (room.max(0.) / offset.abs()).min(1.)
+        }
+    };
+    let x = axis(offset.x, min.x - bounds.min_x(), bounds.max_x() - max.x);
+    let y = axis(offset.y, min.y - bounds.min_y(), bounds.max_y() - max.y);
+    x.min(y)
+}
+
+/// A decay resolved for one transition. Like springs it drives progress from 0 to 1, here
+/// along `1 - e^(-friction * t)`, normalized to land exactly when it comes to rest.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DecayCurve {
+    pub friction: f64,
+    duration: Duration,
+}
+
+impl DecayCurve {
+    /// takes no time without a positive friction, or when `speed` is already at rest
+    pub fn new(friction: f64, speed: f64, rest_velocity: f64) -> Self {
+        let rest_velocity = rest_velocity.max(MIN_DECAY_REST_VELOCITY);
+        let moving = friction.is_finite() && friction > 0. && speed.is_finite();
+        let duration = if moving && speed > rest_velocity {
+            Duration::try_from_secs_f64((speed / rest_velocity).ln() / friction)
+                .unwrap_or(Duration::MAX)
+        } else {
+            Duration::ZERO
+        };
+        Self { friction, duration }
+    }
+
+    /// time until the velocity drops below the rest threshold
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn position(&self, elapsed: Duration) -> f64 {
+        if self.duration.is_zero() {
+            return 1.;
+        }
+        let coast = |t: f64| 1. - (-self.friction * t).exp();
+        coast(elapsed.min(self.duration).as_secs_f64()) / coast(self.duration.as_secs_f64())
+    }
+}
+
+/// A [`DecayConfig`] bound to the value type it flings. Keeps the builder generic while only
+/// types implementing [`Decay`] can be given one.
+#[derive(Clone)]
+pub struct DecayTarget<T: Interpolate> {
+    pub config: DecayConfig,
+    resolve: fn(&DecayConfig, &T) -> (T, DecayCurve),
+}
+
+impl<T: Decay> DecayTarget<T> {
+    pub fn new(config: DecayConfig) -> Self {
+        Self {
+            config,
+            resolve: DecayConfig::resolve,
+        }
+    }
+}
+
+impl<T: Interpolate> DecayTarget<T> {
+    /// where a fling starting at `from` comes to rest, and how it slows down on the way
+    pub fn resolve(&self, from: &T) -> (T, DecayCurve) {
+        (self.resolve)(&self.config, from)
+    }
+}
+
+impl<T: Interpolate> PartialEq for DecayTarget<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+    }
+}
+
+impl<T: Interpolate> fmt::Debug for DecayTarget<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DecayTarget").field(&self.config).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use euclid::Size2D;
+
+    use super::*;
+
+    fn fling(x: f64) -> DecayConfig {
+        DecayConfig::new(Vector2D::new(x, 0.))
+    }
+
+    #[test]
+    fn coasts_velocity_over_friction() {
+        let (to, curve) = fling(900.).resolve(&Point2D::new(0., 0.));
+        assert_eq!(to, Point2D::new(300., 0.));
+        assert!(curve.duration() > Duration::ZERO);
+        assert_eq!(curve.position(Duration::ZERO), 0.);
+        assert_eq!(curve.position(curve.duration()), 1.);
+    }
+
+    #[test]
+    fn bounds_stop_the_fling_at_the_edge() {
+        let bounds = Rect::new(Point2D::new(0., 0.), Size2D::new(100., 100.));
+        let from = Rect::new(Point2D::new(10., 10.), Size2D::new(20., 20.));
+        let (to, _) = fling(900.).with_bounds(bounds).resolve(&from);
+        assert_eq!(to.origin, Point2D::new(80., 10.));
+    }
+
+    #[test]
+    fn flings_at_rest_stay_put() {
+        let from = Point2D::new(5., 5.);
+        let (to, curve) = fling(DEFAULT_DECAY_REST_VELOCITY / 2.).resolve(&from);
+        assert_eq!(to, from);
+        assert_eq!(curve.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn non_finite_velocities_are_ignored() {
+        let from = Point2D::new(5., 5.);
+        for velocity in [f64::NAN, f64::INFINITY] {
+            let (to, curve) = fling(velocity).resolve(&from);
+            assert_eq!(to, from);
+            assert_eq!(curve.duration(), Duration::ZERO);
+        }
+        let curve = DecayCurve::new(DEFAULT_DECAY_FRICTION, f64::INFINITY, 1.);
+        assert_eq!(curve.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn flings_without_friction_stay_put() {
+        let from = Point2D::new(5., 5.);
+        for friction in [0., -1., f64::NAN, f64::INFINITY] {
+            let (to, curve) = fling(900.).with_friction(friction).resolve(&from);
+            assert_eq!(to, from);
+            assert_eq!(curve.duration(), Duration::ZERO);
+            assert_eq!(curve.position(Duration::ZERO), 1.);
+        }
+    }
+
+    #[test]
+    fn rest_velocity_never_reaches_zero() {
+        let from = Point2D::new(0., 0.);
+        let floor = fling(900.)
+            .with_rest_velocity(MIN_DECAY_REST_VELOCITY)
+            .resolve(&from);
+        for rest_velocity in [0., -1., f64::NAN] {
+            let (to, curve) = fling(900.).with_rest_velocity(rest_velocity).resolve(&from);
+            assert_eq!(to, Point2D::new(300., 0.));
+            // still eases in instead of jumping to the end
+            assert_eq!(curve, floor.1);
+            assert!(curve.position(Duration::from_millis(16)) < 0.1);
+        }
+    }
+}
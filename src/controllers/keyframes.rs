@@ -0,0 +1,70 @@
+use crate::{easing::Easing, interpolate::Interpolate};
+
+/// A stop along a keyframe animation. `offset` is the fraction of the duration, `0.0..=1.0`,
+/// at which the value is reached. `easing` shapes the segment from this keyframe to the next,
+/// like css `animation-timing-function`, and falls back to the builder's easing.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Keyframe<T> {
+    pub offset: f64,
+    pub value: T,
+    pub easing: Option<Easing>,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(offset: f64, value: T) -> Self {
+        Self {
+            offset: offset.clamp(0., 1.),
+            value,
+            easing: None,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = Some(easing);
+        self
+    }
+}
+
+/// sorts by offset and fills in the endpoints, so the result always spans `0.0..=1.0`
+pub(crate) fn resolve_keyframes<T: Interpolate>(
+    mut keyframes: Vec<Keyframe<T>>,
+    from: &T,
+    to: &T,
+    default_easing: &Easing,
+) -> Vec<Keyframe<T>> {
+    if keyframes.is_empty() {
+        return keyframes;
+    }
+    keyframes.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    if keyframes[0].offset > 0. {
+        keyframes.insert(0, Keyframe::new(0., from.clone()));
+    }
+    if keyframes[keyframes.len() - 1].offset < 1. {
+        keyframes.push(Keyframe::new(1., to.clone()));
+    }
+    for keyframe in keyframes.iter_mut() {
+        keyframe
+            .easing
+            .get_or_insert_with(|| default_easing.clone());
+    }
+    keyframes
+}
+
+/// `progress` is the linear progress of the whole animation
+pub(crate) fn sample_keyframes<T: Interpolate>(keyframes: &[Keyframe<T>], progress: f64) -> T {
+    let next = keyframes
+        .iter()
+        .position(|keyframe| keyframe.offset > progress)
+        .unwrap_or(keyframes.len() - 1)
+        .max(1);
+    let (start, end) = (&keyframes[next - 1], &keyframes[next]);
+    let span = end.offset - start.offset;
+    if span <= 0. {
+        return end.value.clone();
+    }
+    let local_progress = ((progress - start.offset) / span).clamp(0., 1.);
+    let eased = start.easing.as_ref().map_or(local_progress, |easing| {
+        easing.ease(local_progress as f32) as f64
+    });
+    start.value.interpolate(&end.value, eased)
+}
@@ -6,3 +6,24 @@ pub use animation::*;
 
 mod animation_queue;
 pub use animation_queue::*;
+
+mod animation_handle;
+pub use animation_handle::*;
+
+mod animation_player;
+pub use animation_player::*;
+
+mod timeline;
+pub use timeline::*;
+
+mod channels;
+pub use channels::*;
+
+mod keyframes;
+pub use keyframes::*;
+
+mod spring;
+pub use spring::*;
+
+mod decay;
+pub use decay::*;
@@ -0,0 +1,271 @@
+use web_time::Duration;
+
+/// springs that haven't settled by then are cut off
+pub const MAX_SPRING_DURATION: Duration = Duration::from_secs(10);
+const SETTLE_SCAN_STEP: Duration = Duration::from_millis(1);
+/// stiffness and mass are kept above this, at zero the closed form divides by zero
+const MIN_SPRING_PARAMETER: f64 = 1e-6;
+
+/// Physical spring parameters. The spring pulls progress from 0 to 1, so the rest thresholds
+/// are fractions of the distance travelled rather than pixels.
+///
+/// Stiffness and mass must be positive and damping can't be negative. Out of range values are
+/// clamped, and values that aren't finite fall back to the defaults.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpringConfig {
+    pub stiffness: f64,
+    pub damping: f64,
+    pub mass: f64,
+    /// settled once the distance left to travel is below this...
+    pub rest_delta: f64,
+    /// ...and the speed, in distances per second, is below this
+    pub rest_velocity: f64,
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self::new(170., 26.)
+    }
+}
+
+impl SpringConfig {
+    pub fn new(stiffness: f64, damping: f64) -> Self {
+        Self {
+            stiffness,
+            damping,
+            mass: 1.,
+            rest_delta: 0.001,
+            rest_velocity: 0.01,
+        }
+        .sanitized()
+    }
+
+    pub fn gentle() -> Self {
+        Self::new(120., 14.)
+    }
+
+    pub fn wobbly() -> Self {
+        Self::new(180., 12.)
+    }
+
+    pub fn stiff() -> Self {
+        Self::new(210., 20.)
+    }
+
+    pub fn with_mass(mut self, mass: f64) -> Self {
+        self.mass = mass;
+        self.sanitized()
+    }
+
+    pub fn with_rest_thresholds(mut self, rest_delta: f64, rest_velocity: f64) -> Self {
+        self.rest_delta = rest_delta;
+        self.rest_velocity = rest_velocity;
+        self.sanitized()
+    }
+
+    /// the fields are public, so this runs again when a spring is resolved
+    fn sanitized(self) -> Self {
+        let valid = |value: f64, min: f64, default: f64| {
+            if value.is_finite() {
+                value.max(min)
+            } else {
+                tracing::error!("invalid spring parameter {}, using {}", value, default);
+                default
+            }
+        };
+        Self {
+            stiffness: valid(self.stiffness, MIN_SPRING_PARAMETER, 170.),
+            damping: valid(self.damping, 0., 26.),
+            mass: valid(self.mass, MIN_SPRING_PARAMETER, 1.),
+            rest_delta: valid(self.rest_delta, 0., 0.001),
+            rest_velocity: valid(self.rest_velocity, 0., 0.01),
+        }
+    }
+}
+
+/// A spring resolved for one transition. Sampled in closed form, so it is a pure function of
+/// elapsed time like every other timeline.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Spring {
+    pub config: SpringConfig,
+    /// progress per second at the start of the transition
+    pub initial_velocity: f64,
+    /// also settles the response to velocity inherited from an interrupted motion, see
+    /// [`Self::momentum_displacement`]
+    momentum: bool,
+    settle_duration: Duration,
+}
+
+impl Spring {
+    pub fn new(config: SpringConfig, initial_velocity: f64) -> Self {
+        let mut spring = Self {
+            config: config.sanitized(),
+            initial_velocity: if initial_velocity.is_finite() {
+                initial_velocity
+            } else {
+                0.
+            },
+            momentum: false,
+            settle_duration: MAX_SPRING_DURATION,
+        };
+        spring.settle_duration = spring.find_settle_duration();
+        spring
+    }
+
+    /// keeps going until momentum inherited at the start has died down as well
+    pub fn carrying_momentum(mut self) -> Self {
+        self.momentum = true;
+        self.settle_duration = self.find_settle_duration();
+        self
+    }
+
+    /// how far a value starting at rest on its target with a velocity of 1 per second is
+    /// carried off it at `elapsed`. scaled by the actual velocity, this adds momentum in any
+    /// direction, not only along the path to the target
+    pub fn momentum_displacement(&self, elapsed: Duration) -> f64 {
+        self.oscillate(0., 1., elapsed.as_secs_f64()).0
+    }
+
+    pub fn settle_duration(&self) -> Duration {
+        self.settle_duration
+    }
+
+    /// progress at `elapsed`. overshoots past 1 when underdamped
+    pub fn position(&self, elapsed: Duration) -> f64 {
+        1. + self.displacement_and_velocity(elapsed.as_secs_f64()).0
+    }
+
+    /// progress per second at `elapsed`
+    pub fn velocity(&self, elapsed: Duration) -> f64 {
+        self.displacement_and_velocity(elapsed.as_secs_f64()).1
+    }
+
+    pub fn is_settled_at(&self, elapsed: Duration) -> bool {
+        let t = elapsed.as_secs_f64();
+        let at_rest = |(displacement, velocity): (f64, f64)| {
+            displacement.abs() < self.config.rest_delta
+                && velocity.abs() < self.config.rest_velocity
+        };
+        at_rest(self.displacement_and_velocity(t))
+            && (!self.momentum || at_rest(self.oscillate(0., 1., t)))
+    }
+
+    fn find_settle_duration(&self) -> Duration {
+        let mut elapsed = Duration::ZERO;
+        while elapsed < MAX_SPRING_DURATION {
+            if self.is_settled_at(elapsed) {
+                return elapsed;
+            }
+            elapsed += SETTLE_SCAN_STEP;
+        }
+        MAX_SPRING_DURATION
+    }
+
+    /// damped harmonic oscillator starting 1 away from rest
+    fn displacement_and_velocity(&self, t: f64) -> (f64, f64) {
+        self.oscillate(-1., self.initial_velocity, t)
+    }
+
+    /// displacement from rest and velocity at `t`, starting at `x0` moving at `v0`
+    fn oscillate(&self, x0: f64, v0: f64, t: f64) -> (f64, f64) {
+        let SpringConfig {
+            stiffness,
+            damping,
+            mass,
+            ..
+        } = self.config;
+        let omega = (stiffness / mass).sqrt();
+        let zeta = damping / (2. * (stiffness * mass).sqrt());
+
+        if zeta < 1. {
+            let omega_d = omega * (1. - zeta * zeta).sqrt();
+            let a = x0;
+            let b = (v0 + zeta * omega * x0) / omega_d;
+            let decay = (-zeta * omega * t).exp();
+            let (sin, cos) = (omega_d * t).sin_cos();
+            let displacement = decay * (a * cos + b * sin);
+            let velocity =
+                decay * (-zeta * omega * (a * cos + b * sin) + omega_d * (b * cos - a * sin));
+            (displacement, velocity)
+        } else if zeta == 1. {
+            let a = x0;
+            let b = v0 + omega * x0;
+            let decay = (-omega * t).exp();
+            let displacement = decay * (a + b * t);
+            let velocity = decay * (b - omega * (a + b * t));
+            (displacement, velocity)
+        } else {
+            let root = (zeta * zeta - 1.).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            let c2 = (v0 - r1 * x0) / (r2 - r1);
+            let c1 = x0 - c2;
+            let (e1, e2) = ((r1 * t).exp(), (r2 * t).exp());
+            (c1 * e1 + c2 * e2, c1 * r1 * e1 + c2 * r2 * e2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settles_on_the_target() {
+        for config in [
+            SpringConfig::default(),
+            SpringConfig::gentle(),
+            SpringConfig::wobbly(),
+            SpringConfig::stiff(),
+            SpringConfig::new(100., 40.),
+        ] {
+            let spring = Spring::new(config.clone(), 0.);
+            assert!(spring.settle_duration() < MAX_SPRING_DURATION, "{config:?}");
+            assert!(spring.position(Duration::ZERO).abs() < 1e-9);
+            let settled = spring.position(spring.settle_duration());
+            assert!((settled - 1.).abs() < config.rest_delta, "{config:?}");
+        }
+    }
+
+    #[test]
+    fn initial_velocity_is_the_starting_slope() {
+        let spring = Spring::new(SpringConfig::default(), 3.);
+        assert!((spring.velocity(Duration::ZERO) - 3.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn momentum_leaves_and_returns_to_rest() {
+        let spring = Spring::new(SpringConfig::default(), 0.).carrying_momentum();
+        assert_eq!(spring.momentum_displacement(Duration::ZERO), 0.);
+        // starts off at the velocity it was given
+        let early = spring.momentum_displacement(Duration::from_micros(100));
+        assert!((early / 1e-4 - 1.).abs() < 0.01);
+        assert!(spring.momentum_displacement(spring.settle_duration()).abs() < 0.001);
+    }
+
+    #[test]
+    fn invalid_parameters_are_clamped() {
+        let configs = [
+            SpringConfig::new(0., 10.),
+            SpringConfig::new(-50., -1.),
+            SpringConfig::new(f64::NAN, f64::INFINITY),
+            SpringConfig::default().with_mass(0.),
+            SpringConfig::default().with_mass(-2.),
+            SpringConfig::default().with_mass(f64::NAN),
+        ];
+        for config in configs {
+            assert!(config.stiffness > 0. && config.mass > 0. && config.damping >= 0.);
+            let spring = Spring::new(config.clone(), 0.);
+            let position = spring.position(Duration::from_millis(100));
+            assert!(position.is_finite(), "{config:?}");
+        }
+        // the fields are public, resolving sanitizes them again
+        let config = SpringConfig {
+            mass: 0.,
+            ..SpringConfig::default()
+        };
+        let spring = Spring::new(config, f64::NAN);
+        assert!(spring.position(Duration::from_millis(100)).is_finite());
+        assert!(spring.settle_duration() < MAX_SPRING_DURATION);
+    }
+}
@@ -0,0 +1,335 @@
+use euclid::Rect;
+use web_time::Duration;
+
+use crate::interpolate::Interpolate;
+
+use super::{AnimationBuilder, AnimationTransition, Channels};
+
+/// A pure function of time. Implementors hold no clock and can be sampled at any elapsed
+/// time, in any order, without a runtime.
+pub trait Timeline {
+    type Value;
+
+    fn sample(&self, elapsed: Duration) -> Self::Value;
+
+    fn duration(&self) -> Duration;
+
+    fn is_finished_at(&self, elapsed: Duration) -> bool {
+        elapsed >= self.duration()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Arrangement {
+    Sequence,
+    Parallel,
+}
+
+#[derive(Clone, PartialEq)]
+enum TimelineItem<K, T: Interpolate> {
+    Animation(K, Box<AnimationBuilder<T>>),
+    Group(TimelineBuilder<K, T>),
+}
+
+#[derive(Clone, PartialEq)]
+struct TimelineEntry<K, T: Interpolate> {
+    offset: Duration,
+    item: TimelineItem<K, T>,
+}
+
+/// Builders for several targets arranged in nested sequence and parallel groups, played
+/// as one unit. `K` tells the targets apart, the hooks use controller signals.
+/// "A, then B and C together, then D":
+///
+/// ```ignore
+/// TimelineBuilder::sequence()
+///     .add(a, slide_in)
+///     .add_group(TimelineBuilder::parallel().add(b, grow).add(c, fade_in))
+///     .add(d, slide_out)
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct TimelineBuilder<K, T: Interpolate = Rect<f64, f64>> {
+    arrangement: Arrangement,
+    entries: Vec<TimelineEntry<K, T>>,
+}
+
+impl<K: Clone + PartialEq, T: Interpolate> TimelineBuilder<K, T> {
+    /// each entry starts when the one before it ends
+    pub fn sequence() -> Self {
+        Self {
+            arrangement: Arrangement::Sequence,
+            entries: Vec::new(),
+        }
+    }
+
+    /// every entry starts with the group, the group ends with the last of them
+    pub fn parallel() -> Self {
+        Self {
+            arrangement: Arrangement::Parallel,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn add(self, target: K, anim: AnimationBuilder<T>) -> Self {
+        self.add_with_offset(Duration::ZERO, target, anim)
+    }
+
+    /// `offset` is a gap after the previous entry in a sequence, or a delay from the start of
+    /// a parallel group
+    pub fn add_with_offset(
+        mut self,
+        offset: Duration,
+        target: K,
+        anim: AnimationBuilder<T>,
+    ) -> Self {
+        self.entries.push(TimelineEntry {
+            offset,
+            item: TimelineItem::Animation(target, Box::new(anim)),
+        });
+        self
+    }
+
+    pub fn add_group(self, group: TimelineBuilder<K, T>) -> Self {
+        self.add_group_with_offset(Duration::ZERO, group)
+    }
+
+    pub fn add_group_with_offset(mut self, offset: Duration, group: TimelineBuilder<K, T>) -> Self {
+        self.entries.push(TimelineEntry {
+            offset,
+            item: TimelineItem::Group(group),
+        });
+        self
+    }
+
+    /// how long this plays before it's resolved against where the targets are, see
+    /// [`AnimationBuilder::planned_duration`]. animations repeating forever keep the timeline
+    /// open until it's cancelled
+    pub fn planned_duration(&self) -> Duration {
+        self.arrange(Duration::ZERO, &mut |start, item| match item {
+            TimelineItem::Animation(_, anim) => start.saturating_add(anim.planned_duration()),
+            TimelineItem::Group(group) => start.saturating_add(group.planned_duration()),
+        })
+    }
+
+    /// resolves every animation from where its target is when it starts. `initial` tells
+    /// where a target is before the timeline. sequences move on when the resolved transitions
+    /// end, so a fling stopped short by its bounds leaves no gap
+    pub fn resolve(
+        &self,
+        mut initial: impl FnMut(&K) -> (Option<T>, Channels),
+    ) -> ResolvedTimeline<K, T> {
+        let mut tracks = Vec::new();
+        let duration = self.layout(Duration::ZERO, &mut tracks, &mut initial);
+        ResolvedTimeline { tracks, duration }
+    }
+
+    /// resolves the tree onto `tracks` from `start`, returning when this group ends
+    fn layout(
+        &self,
+        start: Duration,
+        tracks: &mut Vec<Track<K, T>>,
+        initial: &mut impl FnMut(&K) -> (Option<T>, Channels),
+    ) -> Duration {
+        self.arrange(start, &mut |entry_start, item| match item {
+            // delays only take up time
+            TimelineItem::Animation(_, anim) if anim.is_delay() => {
+                entry_start.saturating_add(anim.planned_duration())
+            }
+            TimelineItem::Animation(target, anim) => {
+                let index = match tracks.iter().position(|track| track.target == *target) {
+                    Some(index) => index,
+                    None => {
+                        let (value, channels) = initial(target);
+                        tracks.push(Track {
+                            target: target.clone(),
+                            initial_value: value,
+                            initial_channels: channels,
+                            segments: Vec::new(),
+                        });
+                        tracks.len() - 1
+                    }
+                };
+                let duration = tracks[index].resolve(entry_start, (**anim).clone());
+                entry_start.saturating_add(duration)
+            }
+            TimelineItem::Group(group) => group.layout(entry_start, tracks, initial),
+        })
+    }
+
+    /// lays the entries out from `start`. `end_of` gets each entry's start and returns when
+    /// it ends
+    fn arrange(
+        &self,
+        start: Duration,
+        end_of: &mut impl FnMut(Duration, &TimelineItem<K, T>) -> Duration,
+    ) -> Duration {
+        let mut end = start;
+        for entry in &self.entries {
+            let entry_start = match self.arrangement {
+                Arrangement::Sequence => end,
+                Arrangement::Parallel => start,
+            }
+            .saturating_add(entry.offset);
+            let entry_end = end_of(entry_start, &entry.item);
+            end = match self.arrangement {
+                Arrangement::Sequence => entry_end,
+                Arrangement::Parallel => end.max(entry_end),
+            };
+        }
+        end
+    }
+}
+
+/// A [`TimelineBuilder`] with every animation resolved, so any point in time can be sampled
+pub struct ResolvedTimeline<K, T: Interpolate = Rect<f64, f64>> {
+    /// one per target, in the order they first show up
+    pub tracks: Vec<Track<K, T>>,
+    pub duration: Duration,
+}
+
+/// one target's transitions by start time
+pub struct Track<K, T: Interpolate = Rect<f64, f64>> {
+    pub target: K,
+    initial_value: Option<T>,
+    initial_channels: Channels,
+    segments: Vec<(Duration, AnimationTransition<T>)>,
+}
+
+impl<K, T: Interpolate> Track<K, T> {
+    /// a later transition takes over from wherever the earlier one is
+    pub fn frame_at(&self, elapsed: Duration) -> (Option<T>, Channels) {
+        match self
+            .segments
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= elapsed)
+        {
+            Some((start, transition)) => {
+                let local = elapsed - *start;
+                if transition.is_finished_at(local) {
+                    (
+                        Some(transition.final_value().clone()),
+                        transition.final_channels().clone(),
+                    )
+                } else {
+                    (
+                        Some(transition.sample(local)),
+                        transition.sample_channels(local),
+                    )
+                }
+            }
+            None => (self.initial_value.clone(), self.initial_channels.clone()),
+        }
+    }
+
+    /// starts `anim` at `start` from where the track is then, returning how long it plays
+    fn resolve(&mut self, start: Duration, anim: AnimationBuilder<T>) -> Duration {
+        let (value, channels) = self.frame_at(start);
+        match AnimationTransition::resolve(anim, value.as_ref(), &channels) {
+            Some(transition) => {
+                let duration = transition.duration();
+                let index = self
+                    .segments
+                    .partition_point(|(segment_start, _)| *segment_start <= start);
+                self.segments.insert(index, (start, transition));
+                duration
+            }
+            None => {
+                tracing::error!("skipping timeline animation that can't start");
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use euclid::{Point2D, Size2D, Vector2D};
+
+    use crate::{controllers::DecayConfig, easing::Easing};
+
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn tween(to: f64, millis: u64) -> AnimationBuilder<f64> {
+        AnimationBuilder::default()
+            .animate_to(to)
+            .with_duration(ms(millis))
+            .with_easing(Easing::Linear)
+    }
+
+    fn value_at(timeline: &ResolvedTimeline<&str, f64>, target: &str, millis: u64) -> Option<f64> {
+        let track = timeline
+            .tracks
+            .iter()
+            .find(|track| track.target == target)?;
+        track.frame_at(ms(millis)).0
+    }
+
+    #[test]
+    fn sequences_wait_for_the_longest_of_a_parallel_group() {
+        // A, then B and C together, then D
+        let builder = TimelineBuilder::sequence()
+            .add("a", tween(100., 1000))
+            .add_group(
+                TimelineBuilder::parallel()
+                    .add("b", tween(100., 500))
+                    .add_with_offset(ms(200), "c", tween(100., 600)),
+            )
+            .add("d", tween(100., 1000));
+        let timeline = builder.resolve(|_| (Some(0.), Channels::default()));
+        assert_eq!(timeline.duration, ms(2800));
+        assert_eq!(builder.planned_duration(), ms(2800));
+
+        assert_eq!(value_at(&timeline, "a", 500), Some(50.));
+        assert_eq!(value_at(&timeline, "b", 1000), Some(0.));
+        assert_eq!(value_at(&timeline, "b", 1250), Some(50.));
+        // c starts 200ms into the group
+        assert_eq!(value_at(&timeline, "c", 1200), Some(0.));
+        assert_eq!(value_at(&timeline, "c", 1500), Some(50.));
+        assert_eq!(value_at(&timeline, "d", 1800), Some(0.));
+        assert_eq!(value_at(&timeline, "d", 2300), Some(50.));
+        assert_eq!(value_at(&timeline, "d", 5000), Some(100.));
+    }
+
+    #[test]
+    fn later_animations_start_where_the_target_is() {
+        let timeline = TimelineBuilder::sequence()
+            .add("a", tween(100., 1000))
+            .add_with_offset(ms(500), "a", tween(0., 1000))
+            .resolve(|_| (Some(0.), Channels::default()));
+        assert_eq!(timeline.tracks.len(), 1);
+        assert_eq!(timeline.duration, ms(2500));
+        assert_eq!(value_at(&timeline, "a", 1200), Some(100.));
+        assert_eq!(value_at(&timeline, "a", 2000), Some(50.));
+    }
+
+    #[test]
+    fn sequences_move_on_when_a_fling_stops_short() {
+        let point = |x: f64| Point2D::new(x, 0.);
+        let bounds = Rect::new(point(0.), Size2D::new(10., 10.));
+        let fling = AnimationBuilder::default()
+            .decay(DecayConfig::new(Vector2D::new(900., 0.)).with_bounds(bounds));
+        let builder = TimelineBuilder::sequence().add(0, fling).add(
+            1,
+            AnimationBuilder::default()
+                .animate_to(point(1.))
+                .with_duration(ms(1000))
+                .with_easing(Easing::Linear),
+        );
+        let timeline = builder.resolve(|_| (Some(point(0.)), Channels::default()));
+        assert!(timeline.duration < builder.planned_duration());
+
+        // the next animation starts right as the fling comes to rest at the edge
+        let fling_end = timeline.duration - ms(1000);
+        assert_eq!(timeline.tracks[0].frame_at(fling_end).0, Some(point(10.)));
+        assert_eq!(timeline.tracks[1].frame_at(fling_end).0, Some(point(0.)));
+        assert_eq!(
+            timeline.tracks[1].frame_at(fling_end + ms(500)).0,
+            Some(point(0.5))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::interpolate::Interpolate;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CssUnit {
+    Px,
+    Percent,
+    Em,
+    Rem,
+    Vw,
+    Vh,
+    /// unitless, e.g. `line-height: 1.5`
+    Number,
+}
+
+impl CssUnit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Px => "px",
+            Self::Percent => "%",
+            Self::Em => "em",
+            Self::Rem => "rem",
+            Self::Vw => "vw",
+            Self::Vh => "vh",
+            Self::Number => "",
+        }
+    }
+}
+
+/// A numeric css value with its unit
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CssLength {
+    pub value: f64,
+    pub unit: CssUnit,
+}
+
+impl CssLength {
+    pub const fn new(value: f64, unit: CssUnit) -> Self {
+        Self { value, unit }
+    }
+
+    pub const fn px(value: f64) -> Self {
+        Self::new(value, CssUnit::Px)
+    }
+
+    pub const fn percent(value: f64) -> Self {
+        Self::new(value, CssUnit::Percent)
+    }
+
+    pub const fn em(value: f64) -> Self {
+        Self::new(value, CssUnit::Em)
+    }
+
+    pub const fn rem(value: f64) -> Self {
+        Self::new(value, CssUnit::Rem)
+    }
+
+    pub const fn vw(value: f64) -> Self {
+        Self::new(value, CssUnit::Vw)
+    }
+
+    pub const fn vh(value: f64) -> Self {
+        Self::new(value, CssUnit::Vh)
+    }
+
+    pub const fn number(value: f64) -> Self {
+        Self::new(value, CssUnit::Number)
+    }
+
+    pub fn to_css(&self) -> String {
+        format!("{}{}", self.value, self.unit.suffix())
+    }
+}
+
+impl Interpolate for CssLength {
+    /// lengths in different units can't be blended without layout, so they switch units
+    /// halfway. a zero length takes on the other side's unit and blends normally.
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        let (from_unit, to_unit) = match (self.value == 0., to.value == 0.) {
+            (true, _) => (to.unit, to.unit),
+            (_, true) => (self.unit, self.unit),
+            _ => (self.unit, to.unit),
+        };
+        if from_unit != to_unit {
+            return if t < 0.5 { *self } else { *to };
+        }
+        Self::new(self.value.interpolate(&to.value, t), to_unit)
+    }
+}
+
+impl FromStr for CssLength {
+    type Err = CssLengthParseError;
+
+    /// parses values like `12px`, `1.5em`, `-.5rem`, `1e2%` or `0`
+    fn from_str(css: &str) -> Result<Self, Self::Err> {
+        let css = css.trim();
+        let error = || CssLengthParseError {
+            input: css.to_string(),
+        };
+        let (value, suffix) = css.split_at(number_len(css));
+        let value = value.parse::<f64>().map_err(|_| error())?;
+        let unit = match suffix.to_ascii_lowercase().as_str() {
+            "px" => CssUnit::Px,
+            "%" => CssUnit::Percent,
+            "em" => CssUnit::Em,
+            "rem" => CssUnit::Rem,
+            "vw" => CssUnit::Vw,
+            "vh" => CssUnit::Vh,
+            "" => CssUnit::Number,
+            _ => return Err(error()),
+        };
+        Ok(Self::new(value, unit))
+    }
+}
+
+/// length of the css `<number>` at the start of `css`: an optional sign, digits with an
+/// optional fraction, and an optional exponent. `1em` stops before the `e`
+fn number_len(css: &str) -> usize {
+    let bytes = css.as_bytes();
+    let digits_from = |start: usize| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count()
+    };
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let integer_end = digits_from(end);
+    let mut has_digits = integer_end > end;
+    end = integer_end;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_end = digits_from(end + 1);
+        if fraction_end > end + 1 {
+            has_digits = true;
+            end = fraction_end;
+        }
+    }
+    if !has_digits {
+        return 0;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+        let exponent_end = digits_from(end + 1 + sign);
+        if exponent_end > end + 1 + sign {
+            end = exponent_end;
+        }
+    }
+    end
+}
+
+/// whether `name` can be written as a css property without escaping: an identifier such as
+/// `padding-left`, or a custom property such as `--gap`
+pub fn is_css_property_name(name: &str) -> bool {
+    let name_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if let Some(custom) = name.strip_prefix("--") {
+        return !custom.is_empty() && custom.chars().all(name_char);
+    }
+    // vendor prefixed like `-webkit-line-clamp`
+    let ident = name.strip_prefix('-').unwrap_or(name);
+    ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && ident.chars().all(name_char)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CssLengthParseError {
+    input: String,
+}
+
+impl fmt::Display for CssLengthParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported css length: {:?}", self.input)
+    }
+}
+
+impl Error for CssLengthParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_css_number_grammar() {
+        assert_eq!("12px".parse(), Ok(CssLength::px(12.)));
+        assert_eq!("1.5em".parse(), Ok(CssLength::em(1.5)));
+        assert_eq!("-.5rem".parse(), Ok(CssLength::rem(-0.5)));
+        assert_eq!("1e2px".parse(), Ok(CssLength::px(100.)));
+        assert_eq!("2.5E-1%".parse(), Ok(CssLength::percent(0.25)));
+        assert_eq!("+3".parse(), Ok(CssLength::number(3.)));
+        assert_eq!("1em".parse(), Ok(CssLength::em(1.)));
+        assert!("px".parse::<CssLength>().is_err());
+        assert!("1.px".parse::<CssLength>().is_err());
+        assert!("1e".parse::<CssLength>().is_err());
+        assert!("inf".parse::<CssLength>().is_err());
+        assert!("NaNpx".parse::<CssLength>().is_err());
+        assert!("12pt".parse::<CssLength>().is_err());
+    }
+
+    #[test]
+    fn property_names_are_plain_identifiers() {
+        for name in ["padding", "font-size", "--gap", "-webkit-line-clamp", "_x"] {
+            assert!(is_css_property_name(name), "{name}");
+        }
+        for name in [
+            "",
+            "--",
+            "-",
+            "1st",
+            "padding;color",
+            "top: 0",
+            "width:",
+            "a b",
+        ] {
+            assert!(!is_css_property_name(name), "{name}");
+        }
+    }
+}
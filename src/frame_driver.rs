@@ -0,0 +1,53 @@
+use futures_channel::oneshot;
+
+/// rAF timestamps jitter by a fraction of a millisecond, so a frame that lands just short of
+/// the cap interval is still accepted instead of being skipped.
+const FRAME_TOLERANCE_MS: f64 = 1.0;
+
+/// Paces animation frames with `requestAnimationFrame` so updates are synced to the display.
+/// `fps_cap` optionally limits how often frames are yielded on top of vsync. a cap of `0`
+/// would never yield again, so it counts as no cap.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FrameDriver {
+    fps_cap: Option<u64>,
+    last_frame: Option<f64>,
+}
+
+impl FrameDriver {
+    pub fn new(fps_cap: Option<u64>) -> Self {
+        Self {
+            fps_cap: fps_cap.filter(|cap| *cap > 0),
+            last_frame: None,
+        }
+    }
+
+    pub fn set_fps_cap(&mut self, fps_cap: Option<u64>) {
+        self.fps_cap = fps_cap.filter(|cap| *cap > 0);
+    }
+
+    /// Resolves on the next display frame allowed by the fps cap with the rAF timestamp in ms.
+    pub async fn next_frame(&mut self) -> f64 {
+        loop {
+            let timestamp = request_frame().await;
+            if let (Some(fps_cap), Some(last_frame)) = (self.fps_cap, self.last_frame) {
+                let min_interval = 1000. / fps_cap as f64;
+                if timestamp - last_frame + FRAME_TOLERANCE_MS < min_interval {
+                    continue;
+                }
+            }
+            self.last_frame = Some(timestamp);
+            return timestamp;
+        }
+    }
+}
+
+async fn request_frame() -> f64 {
+    let (sender, receiver) = oneshot::channel();
+    // the frame request is cancelled if this future is dropped before the callback runs
+    let _frame = gloo_render::request_animation_frame(move |timestamp| {
+        let _ = sender.send(timestamp);
+    });
+    receiver
+        .await
+        .expect("animation frame callback dropped before firing")
+}
@@ -1,2 +1,11 @@
 mod use_flipbook;
 pub use use_flipbook::*;
+
+mod use_timeline;
+pub use use_timeline::*;
+
+mod stagger;
+pub use stagger::*;
+
+mod use_layout_registry;
+pub use use_layout_registry::*;
@@ -0,0 +1,153 @@
+use dioxus::signals::{Readable, Signal};
+use web_time::Duration;
+
+use crate::{
+    controllers::{AnimationBuilder, AnimationHandle},
+    easing::Easing,
+};
+
+use super::UseFlipbook;
+
+/// where a stagger ripples out from
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum StaggerFrom {
+    #[default]
+    First,
+    Last,
+    /// the middle item starts first, both ends last
+    Center,
+    Index(usize),
+}
+
+/// How much later each item in a list starts than its neighbour closer to the origin
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stagger {
+    pub interval: Duration,
+    pub from: StaggerFrom,
+    /// spreads the offsets along a curve instead of evenly. the last item still starts at
+    /// `interval` times its distance from the origin
+    pub easing: Easing,
+}
+
+impl Stagger {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            from: StaggerFrom::First,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn from(mut self, from: StaggerFrom) -> Self {
+        self.from = from;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// the start offset of each of `count` items
+    pub fn offsets(&self, count: usize) -> Vec<Duration> {
+        let origin = match self.from {
+            StaggerFrom::First => 0.,
+            StaggerFrom::Last => count.saturating_sub(1) as f64,
+            StaggerFrom::Center => count.saturating_sub(1) as f64 / 2.,
+            StaggerFrom::Index(index) => index.min(count.saturating_sub(1)) as f64,
+        };
+        let distances: Vec<f64> = (0..count).map(|i| (i as f64 - origin).abs()).collect();
+        let furthest = distances.iter().copied().fold(0., f64::max);
+        distances
+            .into_iter()
+            .map(|distance| {
+                if furthest == 0. {
+                    return Duration::ZERO;
+                }
+                let eased = self.easing.ease((distance / furthest) as f32) as f64;
+                self.interval.mul_f64((eased * furthest).max(0.))
+            })
+            .collect()
+    }
+}
+
+/// Queues one animation per controller, started `stagger` apart. `anim` builds the
+/// animation for the item at each index, so items can differ beyond their start time.
+///
+/// Each controller gets a delay followed by its animation at the back of its queue, so
+/// anything already queued plays first. The handles are for the animations, in the
+/// order of `controllers`.
+pub fn stagger(
+    controllers: &[Signal<UseFlipbook>],
+    stagger: &Stagger,
+    mut anim: impl FnMut(usize) -> AnimationBuilder,
+) -> Vec<AnimationHandle> {
+    controllers
+        .iter()
+        .zip(stagger.offsets(controllers.len()))
+        .enumerate()
+        .map(|(index, (controller, offset))| {
+            let mut controller = controller.peek().clone();
+            if !offset.is_zero() {
+                controller.queue(AnimationBuilder::new_delay(offset));
+            }
+            controller.queue(anim(index))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(offsets: Vec<Duration>) -> Vec<u128> {
+        offsets.iter().map(Duration::as_millis).collect()
+    }
+
+    #[test]
+    fn offsets_grow_with_distance_from_the_origin() {
+        let stagger = Stagger::new(Duration::from_millis(40));
+        assert_eq!(millis(stagger.offsets(4)), [0, 40, 80, 120]);
+        assert_eq!(
+            millis(stagger.clone().from(StaggerFrom::Last).offsets(4)),
+            [120, 80, 40, 0]
+        );
+        assert_eq!(
+            millis(stagger.clone().from(StaggerFrom::Center).offsets(5)),
+            [80, 40, 0, 40, 80]
+        );
+        assert_eq!(
+            millis(stagger.from(StaggerFrom::Index(1)).offsets(4)),
+            [40, 0, 40, 80]
+        );
+    }
+
+    #[test]
+    fn even_counts_have_no_single_center() {
+        let stagger = Stagger::new(Duration::from_millis(40)).from(StaggerFrom::Center);
+        assert_eq!(millis(stagger.offsets(4)), [60, 20, 20, 60]);
+    }
+
+    #[test]
+    fn out_of_range_origins_start_at_the_last_item() {
+        let stagger = Stagger::new(Duration::from_millis(10)).from(StaggerFrom::Index(9));
+        assert_eq!(millis(stagger.offsets(3)), [20, 10, 0]);
+    }
+
+    #[test]
+    fn easing_keeps_the_ends_in_place() {
+        let stagger = Stagger::new(Duration::from_millis(100)).with_easing(Easing::QuadIn);
+        let offsets = millis(stagger.offsets(5));
+        assert_eq!(offsets[0], 0);
+        assert_eq!(offsets[4], 400);
+        // bunched up at the start
+        assert!(offsets[2] < 200);
+    }
+
+    #[test]
+    fn nothing_to_stagger() {
+        let stagger = Stagger::new(Duration::from_millis(40));
+        assert!(stagger.offsets(0).is_empty());
+        assert_eq!(stagger.offsets(1), [Duration::ZERO]);
+    }
+}
@@ -1,24 +1,28 @@
 use std::rc::Rc;
 
+use web_time::Duration;
+
 use dioxus::{
     dioxus_core::Task,
     hooks::{use_effect, use_signal},
-    html::{geometry::euclid::Rect, MountedData},
-    prelude::spawn,
-    signals::{Readable, Signal, Writable},
+    html::{
+        geometry::euclid::{Rect, Vector2D},
+        MountedData,
+    },
+    prelude::{eval, spawn, use_hook, EventHandler},
+    signals::{CopyValue, Readable, Signal, Writable},
 };
 
+pub use crate::controllers::FlipbookStatus;
 use crate::{
-    controllers::{AnimationBuilder, AnimationQueue, AnimationTransition},
-    stopwatch::use_stopwatch_signal,
+    clock::{MonotonicClock, SharedClock},
+    controllers::{
+        AnimationBuilder, AnimationEvent, AnimationEventKind, AnimationHandle, AnimationId,
+        AnimationPlayer, Channels, InterruptPolicy, QueuedAnimation,
+    },
+    frame_driver::FrameDriver,
 };
 
-#[derive(Clone, PartialEq, Debug)]
-pub enum FlipbookStatus {
-    Busy,
-    Resting,
-}
-
 #[derive(Clone, PartialEq, Debug)]
 pub enum FlipbookCommand {
     Resume,
@@ -26,47 +30,213 @@ pub enum FlipbookCommand {
     PlayNow(AnimationBuilder),
     DropAll,
     Queue(AnimationBuilder),
+    /// like `PlayNow`, resolving the entry's handle
+    PlayNowEntry(QueuedAnimation),
+    /// like `Queue`, resolving the entry's handle
+    QueueEntry(QueuedAnimation),
+    Play(QueuedAnimation),
+    SetInterruptPolicy(InterruptPolicy),
+    Insert(usize, QueuedAnimation),
+    Remove(AnimationId),
+    MoveToFront(AnimationId),
+    Replace(AnimationId, AnimationBuilder),
     SetRect(Rect<f64, f64>),
+    SetFrame(Rect<f64, f64>, Channels),
+    SetPlaybackRate(f64),
+    Seek(f64),
+    SeekTime(Duration),
+    Reverse,
+    /// does nothing
     None,
 }
 
+/// Lifecycle callbacks, called from the animation task as the player reports events
+#[derive(Clone, Default, PartialEq)]
+pub struct FlipbookEventHandlers {
+    pub onstart: Option<EventHandler<AnimationEvent>>,
+    pub onframe: Option<EventHandler<AnimationEvent>>,
+    pub oncomplete: Option<EventHandler<AnimationEvent>>,
+    pub oncancel: Option<EventHandler<AnimationEvent>>,
+}
+
+impl FlipbookEventHandlers {
+    fn call(&self, event: AnimationEvent) {
+        let handler = match event.kind {
+            AnimationEventKind::Started => &self.onstart,
+            AnimationEventKind::Frame => &self.onframe,
+            AnimationEventKind::Completed => &self.oncomplete,
+            AnimationEventKind::Cancelled => &self.oncancel,
+        };
+        if let Some(handler) = handler {
+            handler.call(event);
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct UseFlipbook {
     current_rect: Signal<Option<Rect<f64, f64>>>,
+    current_channels: Signal<Channels>,
+    transform_origin: Signal<Option<String>>,
     status: Signal<FlipbookStatus>,
-    command: Signal<FlipbookCommand>,
+    iteration: Signal<u32>,
+    progress: Signal<Option<f64>>,
+    queued: Signal<Vec<(AnimationId, AnimationBuilder)>>,
+    /// buffered so several calls from one event handler all reach the player
+    commands: Signal<Vec<FlipbookCommand>>,
     mounted: Signal<Option<Rc<MountedData>>>,
+    /// not a signal: nothing renders from the handlers, and they are set while rendering
+    event_handlers: CopyValue<FlipbookEventHandlers>,
+    /// where the page flow puts the element, in layout mode
+    layout_rect: Signal<Option<Rect<f64, f64>>>,
+    layout_animation: CopyValue<Option<AnimationBuilder>>,
+    /// bumped to measure the layout again once the render lands
+    layout_requests: Signal<u64>,
 }
 
 impl UseFlipbook {
     /// position is relative until the rect is read from MountedData.
     /// then the position and size are controlled by the animatable and the position is absolute.
+    /// in layout mode the element stays relative, offset from its place in the flow while it
+    /// glides there.
     pub(crate) fn read_render_state(&self) -> String {
-        self.current_rect
-            .read()
-            .as_ref()
-            .map_or("position: relative;".to_string(), |rect| {
-                format!(
-                    "width: {}px; height: {}px; left: {}px; top: {}px;",
-                    rect.size.width, rect.size.height, rect.origin.x, rect.origin.y
-                )
-            })
+        render_state(
+            self.layout_animation.peek().is_some(),
+            *self.layout_rect.read(),
+            *self.current_rect.read(),
+            &self.current_channels.read(),
+            self.transform_origin.read().as_deref(),
+        )
+    }
+
+    /// any css `transform-origin` value. defaults to the browser's `50% 50%`, which layout
+    /// animations assume: they scale about the center
+    pub fn set_transform_origin(&mut self, origin: impl Into<String>) {
+        self.transform_origin.set(Some(origin.into()));
+    }
+
+    pub fn set_event_handlers(&mut self, handlers: FlipbookEventHandlers) {
+        self.event_handlers.set(handlers);
     }
 
     pub fn set_mounted_data(&mut self, data: Rc<MountedData>) {
         self.mounted.set(Some(data));
     }
 
+    /// Layout mode: the element keeps its place in the page flow instead of being positioned
+    /// by the controller, and glides with `anim` to wherever a re-render moves it. `anim`
+    /// supplies the timing and channels, its target is the new layout rect. `None` hands the
+    /// position back to the controller.
+    pub fn set_layout_animation(&mut self, anim: Option<AnimationBuilder>) {
+        self.layout_animation.set(anim);
+    }
+
+    /// Plays `anim` from `from` to where the element was mounted, e.g. from the rect a shared
+    /// element had in the view it left. Call once mounted
+    pub fn enter_from(&self, from: Rect<f64, f64>, anim: AnimationBuilder) {
+        let Some(data) = self.mounted.peek().clone() else {
+            return;
+        };
+        let mut flipbook = self.clone();
+        spawn(async move {
+            if let Some(rect) = flipbook.measure(&data).await {
+                flipbook.play_now(anim.animate_from(from).animate_to(rect));
+            }
+        });
+    }
+
+    /// Measures the layout again after the next render lands, e.g. when a sibling grew and
+    /// pushed the element along. The [`Animatable`] requests this itself whenever its parent
+    /// re-renders it.
+    ///
+    /// [`Animatable`]: crate::components::Animatable
+    pub fn request_layout(&mut self) {
+        *self.layout_requests.write() += 1;
+    }
+
+    /// subscribes to [`Self::request_layout`]
+    pub fn read_layout_requests(&self) -> u64 {
+        *self.layout_requests.read()
+    }
+
+    /// the element's rect. layout mode measures it in the page rather than the viewport, so
+    /// scrolling the window doesn't count as moving. scroll containers inside the page do
+    async fn measure(&self, data: &MountedData) -> Option<Rect<f64, f64>> {
+        let rect = data.get_client_rect().await.ok()?;
+        if self.layout_animation.peek().is_none() {
+            return Some(rect);
+        }
+        Some(rect.translate(window_scroll().await))
+    }
+
+    /// Measures the element and animates to its new layout rect if it moved, the First and
+    /// Last of FLIP. The [`Animatable`] calls this after renders that may have changed layout,
+    /// see [`Self::request_layout`]. Skipped while an animation runs, since the offsets would
+    /// be measured too, and repeated once it comes to rest.
+    ///
+    /// [`Animatable`]: crate::components::Animatable
+    pub fn measure_layout(&self) {
+        let Some(data) = self.mounted.peek().clone() else {
+            return;
+        };
+        let mut flipbook = self.clone();
+        spawn(async move {
+            let Some(anim) = flipbook.layout_animation.peek().clone() else {
+                if flipbook.layout_rect.peek().is_some() {
+                    flipbook.layout_rect.set(None);
+                }
+                return;
+            };
+            if !flipbook.peek_is_finished() {
+                return;
+            }
+            let Some(rect) = flipbook.measure(&data).await else {
+                return;
+            };
+            // a layout animation may have started while measuring
+            if !flipbook.peek_is_finished() {
+                return;
+            }
+            let previous = *flipbook.layout_rect.peek();
+            if previous == Some(rect) {
+                return;
+            }
+            flipbook.layout_rect.set(Some(rect));
+            if previous.is_some() {
+                // renders at the old rect, offset from the new layout, until the animation
+                // takes it there
+                flipbook.play_now(anim.animate_to(rect));
+            } else {
+                flipbook.set_rect(rect);
+            }
+        });
+    }
+
     pub fn peek_rect(&self) -> Option<Rect<f64, f64>> {
-        self.current_rect.peek().clone()
+        *self.current_rect.peek()
     }
 
     pub fn read_rect(&self) -> Option<Rect<f64, f64>> {
-        self.current_rect.read().clone()
+        *self.current_rect.read()
+    }
+
+    pub fn peek_channels(&self) -> Channels {
+        self.current_channels.peek().clone()
+    }
+
+    pub fn read_channels(&self) -> Channels {
+        self.current_channels.read().clone()
     }
 
     pub fn set_rect(&mut self, rect: Rect<f64, f64>) {
-        self.command.set(FlipbookCommand::SetRect(rect));
+        self.send(FlipbookCommand::SetRect(rect));
+    }
+
+    /// places the rect and channels directly, for animations driven from outside the queue
+    /// such as a [`TimelineBuilder`](crate::controllers::TimelineBuilder). anything running or queued is
+    /// cancelled
+    pub fn set_frame(&mut self, rect: Rect<f64, f64>, channels: Channels) {
+        self.send(FlipbookCommand::SetFrame(rect, channels));
     }
 
     pub fn peek_status(&self) -> FlipbookStatus {
@@ -85,45 +255,276 @@ impl UseFlipbook {
         self.status.read().clone() == FlipbookStatus::Resting
     }
 
-    pub fn queue(&mut self, anim: AnimationBuilder) {
-        self.command.set(FlipbookCommand::Queue(anim));
+    /// iteration of the running animation when it repeats, counting from 0
+    pub fn peek_iteration(&self) -> u32 {
+        *self.iteration.peek()
+    }
+
+    pub fn read_iteration(&self) -> u32 {
+        *self.iteration.read()
+    }
+
+    /// linear progress of the running animation, `0.0` to `1.0`. `None` while resting
+    pub fn peek_progress(&self) -> Option<f64> {
+        *self.progress.peek()
+    }
+
+    pub fn read_progress(&self) -> Option<f64> {
+        *self.progress.read()
+    }
+
+    /// jumps the running animation to `progress`, `0.0` to `1.0`. works while paused,
+    /// so a slider can scrub through it
+    pub fn seek(&mut self, progress: f64) {
+        self.send(FlipbookCommand::Seek(progress));
+    }
+
+    /// jumps to `elapsed` into the animation played forward, so after [`Self::reverse`] it still
+    /// lands where `seek` with the same share of the duration does
+    pub fn seek_time(&mut self, elapsed: Duration) {
+        self.send(FlipbookCommand::SeekTime(elapsed));
+    }
+
+    /// runs the current animation backwards from where it is, e.g. to close a panel that was
+    /// cancelled halfway open. reversing again heads back to the original destination
+    pub fn reverse(&mut self) {
+        self.send(FlipbookCommand::Reverse);
+    }
+
+    /// await the handle to run something once `anim` is done. dropping it doesn't cancel
+    /// the animation
+    pub fn queue(&mut self, anim: AnimationBuilder) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.send(FlipbookCommand::QueueEntry(entry));
+        handle
+    }
+
+    /// everything running or queued is cancelled, their handles resolve to
+    /// [`AnimationOutcome::Cancelled`](crate::controllers::AnimationOutcome::Cancelled)
+    pub fn play_now(&mut self, anim: AnimationBuilder) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.send(FlipbookCommand::PlayNowEntry(entry));
+        handle
+    }
+
+    /// animations waiting behind the running one, next first
+    pub fn peek_queue(&self) -> Vec<(AnimationId, AnimationBuilder)> {
+        self.queued.peek().clone()
+    }
+
+    pub fn read_queue(&self) -> Vec<(AnimationId, AnimationBuilder)> {
+        self.queued.read().clone()
+    }
+
+    /// queues `anim` at `index` among the waiting animations. past the end appends
+    pub fn insert(&mut self, index: usize, anim: AnimationBuilder) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.send(FlipbookCommand::Insert(index, entry));
+        handle
+    }
+
+    /// cancels one waiting animation without touching the rest of the queue. its handle
+    /// resolves to `Dropped`. the running animation isn't affected
+    pub fn remove(&mut self, id: AnimationId) {
+        self.send(FlipbookCommand::Remove(id));
+    }
+
+    /// the waiting animation plays next
+    pub fn move_to_front(&mut self, id: AnimationId) {
+        self.send(FlipbookCommand::MoveToFront(id));
+    }
+
+    /// swaps a waiting animation for `anim`. it keeps its place, id and handle
+    pub fn replace(&mut self, id: AnimationId, anim: AnimationBuilder) {
+        self.send(FlipbookCommand::Replace(id, anim));
+    }
+
+    /// starts `anim` according to its interrupt policy, or the controller's. `play_now` and
+    /// `queue` are the `Replace` and `Append` policies
+    pub fn play(&mut self, anim: AnimationBuilder) -> AnimationHandle {
+        let (entry, handle) = QueuedAnimation::new(anim);
+        self.send(FlipbookCommand::Play(entry));
+        handle
     }
 
-    pub fn play_now(&mut self, anim: AnimationBuilder) {
-        self.command.set(FlipbookCommand::PlayNow(anim));
+    /// how `play` reacts to rapid input for builders without a policy. `Replace` by default
+    pub fn set_interrupt_policy(&mut self, policy: InterruptPolicy) {
+        self.send(FlipbookCommand::SetInterruptPolicy(policy));
     }
 
     pub fn resume(&mut self) {
-        self.command.set(FlipbookCommand::Resume);
+        self.send(FlipbookCommand::Resume);
     }
 
     pub fn pause(&mut self) {
-        self.command.set(FlipbookCommand::Pause);
+        self.send(FlipbookCommand::Pause);
     }
 
     pub fn drop_all(&mut self) {
-        self.command.set(FlipbookCommand::DropAll);
+        self.send(FlipbookCommand::DropAll);
     }
+
+    /// `1.0` is normal speed. applies to delays too and takes effect mid-animation without
+    /// a jump. negative rates play backwards
+    pub fn set_playback_rate(&mut self, rate: f64) {
+        self.send(FlipbookCommand::SetPlaybackRate(rate));
+    }
+
+    fn send(&mut self, command: FlipbookCommand) {
+        self.commands.write().push(command);
+    }
+}
+
+/// the inline style for a frame: the rect, the channels, then the transform origin
+fn render_state(
+    layout_mode: bool,
+    layout_rect: Option<Rect<f64, f64>>,
+    current_rect: Option<Rect<f64, f64>>,
+    channels: &Channels,
+    transform_origin: Option<&str>,
+) -> String {
+    let (rect_state, layout_transform) = match (layout_mode, layout_rect, current_rect) {
+        // keeps its place in the flow, so the siblings don't move along
+        (true, Some(layout), Some(rect)) if rect != layout => (
+            "position: relative;".to_string(),
+            Some(layout_transform(&layout, &rect)),
+        ),
+        (false, _, Some(rect)) => (
+            format!(
+                "width: {}px; height: {}px; left: {}px; top: {}px;",
+                rect.size.width, rect.size.height, rect.origin.x, rect.origin.y
+            ),
+            None,
+        ),
+        _ => ("position: relative;".to_string(), None),
+    };
+    let transform_origin = transform_origin.map_or(String::new(), |origin| {
+        format!("transform-origin: {};", origin)
+    });
+    format!(
+        "{} {} {}",
+        rect_state,
+        channels.to_css_after_transform(layout_transform),
+        transform_origin
+    )
+}
+
+/// draws an element laid out at `layout` over `rect`, scaling about its center
+fn layout_transform(layout: &Rect<f64, f64>, rect: &Rect<f64, f64>) -> String {
+    let offset = rect.center() - layout.center();
+    let scale = |to: f64, from: f64| if from > 0. { to / from } else { 1. };
+    format!(
+        "translate({}px, {}px) scale({}, {})",
+        offset.x,
+        offset.y,
+        scale(rect.size.width, layout.size.width),
+        scale(rect.size.height, layout.size.height)
+    )
 }
 
-fn use_flipbook() -> UseFlipbook {
+fn use_flipbook(clock: SharedClock) -> UseFlipbook {
     let mut current_rect = use_signal(|| None as Option<Rect<f64, f64>>);
+    let mut current_channels = use_signal(Channels::default);
+    let transform_origin = use_signal(|| None as Option<String>);
     let mut status = use_signal(|| FlipbookStatus::Resting);
-    let mut command = use_signal(|| FlipbookCommand::None);
-
-    let mut anim_handle: Signal<Option<Task>> = use_signal(|| None);
-    let mut stopwatch = use_stopwatch_signal();
+    let mut iteration = use_signal(|| 0u32);
+    let mut progress = use_signal(|| None as Option<f64>);
+    let mut queued = use_signal(Vec::<(AnimationId, AnimationBuilder)>::new);
+    let mut commands = use_signal(Vec::<FlipbookCommand>::new);
+
+    let event_handlers = use_hook(|| CopyValue::new(FlipbookEventHandlers::default()));
+    let mut player = use_signal(|| {
+        let mut player = AnimationPlayer::new(clock);
+        player.record_events(true);
+        player
+    });
+    let mut frame_loop: Signal<Option<Task>> = use_signal(|| None);
+
+    // mirror the player into signals so readers only re-render on change
+    let mut sync_player = move || {
+        let (rect, channels, player_status, player_iteration, player_progress) = {
+            let player = player.peek();
+            (
+                player.value().copied(),
+                player.channels().clone(),
+                player.status(),
+                player.iteration(),
+                player.progress(),
+            )
+        };
+        if *current_rect.peek() != rect {
+            current_rect.set(rect);
+        }
+        if *current_channels.peek() != channels {
+            current_channels.set(channels);
+        }
+        if *status.peek() != player_status {
+            status.set(player_status);
+        }
+        if *iteration.peek() != player_iteration {
+            iteration.set(player_iteration);
+        }
+        if *progress.peek() != player_progress {
+            progress.set(player_progress);
+        }
+        let queue_changed = {
+            let player = player.peek();
+            let queued = queued.peek();
+            !player
+                .queued()
+                .map(|entry| (&entry.id, &entry.builder))
+                .eq(queued.iter().map(|(id, builder)| (id, builder)))
+        };
+        if queue_changed {
+            let entries = player
+                .peek()
+                .queued()
+                .map(|entry| (entry.id, entry.builder.clone()))
+                .collect();
+            queued.set(entries);
+        }
+        // after the signals, so handlers read the state the event describes
+        let events = player.write().take_events();
+        let handlers = event_handlers.peek().clone();
+        for event in events {
+            handlers.call(event);
+        }
+    };
 
-    let mut queue = use_signal(|| AnimationQueue::new());
+    let mut ensure_running = move || {
+        if frame_loop.peek().is_some() || !player.peek().needs_frames() {
+            return;
+        }
+        let handle = spawn(async move {
+            let mut frames = FrameDriver::new(player.peek().fps_cap());
+            loop {
+                player.write().tick();
+                sync_player();
+                if !player.peek().needs_frames() {
+                    break;
+                }
+                frames.set_fps_cap(player.peek().fps_cap());
+                frames.next_frame().await;
+            }
+            frame_loop.set(None);
+        });
+        frame_loop.set(Some(handle));
+    };
 
     let mounted = use_signal(|| None as Option<Rc<MountedData>>);
+    let layout_rect = use_signal(|| None as Option<Rect<f64, f64>>);
+    let layout_animation = use_hook(|| CopyValue::new(None as Option<AnimationBuilder>));
+    let layout_requests = use_signal(|| 0u64);
     let read_mounted = move |data: Option<Rc<MountedData>>| async move {
         let client_rect = data.as_ref().map(|el| el.get_client_rect());
 
         if let Some(client_rect) = client_rect {
             if let Ok(rect) = client_rect.await {
                 tracing::info!("setting rect from mounted data");
-                current_rect.set(Some(rect));
+                player.write().set_value(rect);
+                sync_player();
+                // animations queued before mount were waiting for a starting rect
+                ensure_running();
             }
         }
     };
@@ -134,137 +535,152 @@ fn use_flipbook() -> UseFlipbook {
         });
     });
 
-    let mut spawn_animation = move |mut current_transition: AnimationTransition| {
-        let handle = spawn(async move {
-            status.set(FlipbookStatus::Busy);
-            stopwatch.write().start();
-            current_rect.set(Some(current_transition.from.clone()));
-            while !current_transition.is_finished() {
-                let elapsed = stopwatch.write().get_elapsed();
-                current_rect.set(Some(current_transition.step(elapsed).await));
-            }
-            current_rect.set(Some(current_transition.to));
-            //cleanup
-            anim_handle.set(None);
-            stopwatch.write().clear();
-            status.set(FlipbookStatus::Resting);
-        });
-        anim_handle.set(Some(handle));
-    };
-
-    let mut spawn_delay = move |duration: web_time::Duration| {
-        let handle = spawn(async move {
-            gloo_timers::future::sleep(duration).await;
-            anim_handle.set(None);
-        });
-        anim_handle.set(Some(handle));
-    };
-
-    let mut clear_hooks = move || {
-        anim_handle.write().as_mut().map(|handle| handle.cancel());
-        anim_handle.set(None);
-        stopwatch.write().clear();
-    };
-
-    let mut parse_queue = move || {
-        tracing::info!("evaluating queue: {:?}", queue.peek());
-
-        if let Some(anim_builder) = queue.write().pop_front() {
-            match (anim_builder.from.clone(), anim_builder.to.clone()) {
-                (_, None) => spawn_delay(anim_builder.duration),
-                (None, Some(to)) => {
-                    let from = current_rect.peek().as_ref().unwrap().clone();
-                    if from == to {
-                        tracing::error!(
-                            "requested animation has same origin and destination: {:?} {:?}",
-                            from,
-                            to
-                        );
-                        return;
-                    }
-                    let animation = AnimationTransition::new(anim_builder, from, to);
-                    spawn_animation(animation);
-                }
-                (Some(from), Some(to)) => {
-                    let animation = AnimationTransition::new(anim_builder, from, to);
-                    spawn_animation(animation);
-                }
-            }
-        }
-    };
-
     use_effect(move || {
-        // subscribe to anim handle and play the next animation in queue when the current one is done
-        tracing::info!("reading queue and handle");
-        let queue_read = queue.read().clone();
-        if anim_handle.read().is_some() {
-            tracing::info!("handle is empty");
+        if commands.read().is_empty() {
             return;
         }
-        if queue_read.is_empty() {
-            tracing::info!("queue is empty");
-            return;
-        }
-        parse_queue();
-    });
-
-    use_effect(move || {
-        let cmd = command();
-        tracing::info!("processing command {:?}", cmd);
-        match cmd {
-            FlipbookCommand::Resume => {
-                tracing::info!("command: play: paused animation");
-
-                if let Some(handle) = anim_handle() {
-                    if handle.paused() {
-                        handle.resume();
-                        stopwatch.write().start();
-                    }
+        // taking them re-runs this effect once more, with nothing left to do
+        let pending = std::mem::take(&mut *commands.write());
+        for cmd in pending {
+            tracing::info!("processing command {:?}", cmd);
+            match cmd {
+                FlipbookCommand::Resume => {
+                    tracing::info!("command: play: paused animation");
+                    player.write().resume();
                 }
-                command.set(FlipbookCommand::None);
-            }
-            FlipbookCommand::Pause => {
-                stopwatch.write().stop(); // don't count pause duration as elapsed animation time
-                anim_handle.write().as_mut().map(|handle| handle.pause()); // stop polling loop
-                command.set(FlipbookCommand::None);
-            }
-            FlipbookCommand::DropAll => {
-                clear_hooks();
-                queue.write().drop_all();
-                command.set(FlipbookCommand::None);
-                status.set(FlipbookStatus::Resting);
-            }
-            FlipbookCommand::Queue(anim) => {
-                queue.write().push(anim);
-                command.set(FlipbookCommand::None)
-            }
-            FlipbookCommand::PlayNow(anim) => {
-                clear_hooks();
-                status.set(FlipbookStatus::Busy);
-                queue.write().play_now(anim);
-                tracing::info!("play now!");
-                parse_queue();
-                command.set(FlipbookCommand::None);
-            }
-            FlipbookCommand::SetRect(rect) => {
-                if anim_handle.peek().is_none() {
-                    current_rect.set(Some(rect));
+                FlipbookCommand::Pause => player.write().pause(),
+                FlipbookCommand::DropAll => player.write().drop_all(),
+                FlipbookCommand::Queue(anim) => {
+                    player.write().queue(anim);
+                }
+                FlipbookCommand::QueueEntry(entry) => player.write().queue_entry(entry),
+                FlipbookCommand::Play(entry) => player.write().play_entry(entry),
+                FlipbookCommand::SetInterruptPolicy(policy) => {
+                    player.write().set_interrupt_policy(policy)
+                }
+                FlipbookCommand::Insert(index, entry) => player.write().insert_entry(index, entry),
+                FlipbookCommand::Remove(id) => {
+                    player.write().remove_queued(id);
                 }
-                command.set(FlipbookCommand::None);
+                FlipbookCommand::MoveToFront(id) => {
+                    player.write().move_to_front(id);
+                }
+                FlipbookCommand::Replace(id, anim) => {
+                    player.write().replace_queued(id, anim);
+                }
+                FlipbookCommand::PlayNow(anim) => {
+                    player.write().play_now(anim);
+                    tracing::info!("play now!");
+                }
+                FlipbookCommand::PlayNowEntry(entry) => {
+                    player.write().play_now_entry(entry);
+                    tracing::info!("play now!");
+                }
+                FlipbookCommand::SetRect(rect) => player.write().set_value(rect),
+                FlipbookCommand::SetFrame(rect, channels) => {
+                    player.write().set_frame(rect, channels)
+                }
+                FlipbookCommand::SetPlaybackRate(rate) => player.write().set_playback_rate(rate),
+                FlipbookCommand::Seek(progress) => player.write().seek(progress),
+                FlipbookCommand::SeekTime(elapsed) => player.write().seek_time(elapsed),
+                FlipbookCommand::Reverse => player.write().reverse(),
+                FlipbookCommand::None => {}
             }
-            FlipbookCommand::None => {}
         }
+        sync_player();
+        ensure_running();
     });
 
     UseFlipbook {
         current_rect,
+        current_channels,
+        transform_origin,
         status,
-        command,
+        iteration,
+        progress,
+        queued,
+        commands,
         mounted,
+        event_handlers,
+        layout_rect,
+        layout_animation,
+        layout_requests,
     }
 }
 
+/// zero where there's no window to scroll
+async fn window_scroll() -> Vector2D<f64, f64> {
+    let Ok(scroll) = eval("return [window.scrollX, window.scrollY];")
+        .join()
+        .await
+    else {
+        return Vector2D::zero();
+    };
+    let axis = |index: usize| scroll.get(index).and_then(|value| value.as_f64());
+    Vector2D::new(axis(0).unwrap_or(0.), axis(1).unwrap_or(0.))
+}
+
 pub fn use_flipbook_signal() -> Signal<UseFlipbook> {
-    let ctrl = use_flipbook();
+    use_flipbook_signal_with_clock(MonotonicClock::shared())
+}
+
+/// `clock` is only read on the first render
+pub fn use_flipbook_signal_with_clock(clock: SharedClock) -> Signal<UseFlipbook> {
+    let ctrl = use_flipbook(clock);
     use_signal(|| ctrl)
 }
+
+#[cfg(test)]
+mod tests {
+    use dioxus::html::geometry::euclid::{Point2D, Size2D};
+
+    use super::*;
+
+    fn rect(x: f64, width: f64) -> Rect<f64, f64> {
+        Rect::new(Point2D::new(x, 0.), Size2D::new(width, 10.))
+    }
+
+    #[test]
+    fn transform_origin_follows_the_channels() {
+        let channels = Channels {
+            rotate: Some(90.),
+            ..Channels::default()
+        };
+        assert_eq!(
+            render_state(false, None, Some(rect(5., 20.)), &channels, Some("top left")),
+            "width: 20px; height: 10px; left: 5px; top: 0px; transform: rotate(90deg); transform-origin: top left;"
+        );
+        assert_eq!(
+            render_state(false, None, None, &Channels::default(), None),
+            "position: relative;  "
+        );
+    }
+    #[test]
+    fn layout_mode_draws_the_way_there_with_a_transform() {
+        let layout = rect(100., 20.);
+        let channels = Channels {
+            rotate: Some(90.),
+            ..Channels::default()
+        };
+        // twice as wide around the same center, drawn over the element's spot in the flow
+        assert_eq!(
+            render_state(true, Some(layout), Some(rect(90., 40.)), &channels, None),
+            "position: relative; transform: translate(0px, 0px) scale(2, 1) rotate(90deg); "
+        );
+        assert_eq!(
+            render_state(
+                true,
+                Some(layout),
+                Some(rect(110., 20.)),
+                &Channels::default(),
+                None
+            ),
+            "position: relative; transform: translate(10px, 0px) scale(1, 1); "
+        );
+        // at rest where it's laid out
+        assert_eq!(
+            render_state(true, Some(layout), Some(layout), &channels, None),
+            "position: relative; transform: rotate(90deg); "
+        );
+    }
+}
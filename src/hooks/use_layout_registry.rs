@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use dioxus::{
+    dioxus_core::ScopeId,
+    hooks::use_context_provider,
+    html::geometry::euclid::Rect,
+    prelude::current_scope_id,
+    signals::{CopyValue, Readable, Writable},
+};
+
+use crate::frame_driver::FrameDriver;
+
+#[derive(Clone, Copy)]
+struct SharedRect {
+    rect: Rect<f64, f64>,
+    /// set once the element it belongs to unmounted or changed its `layout_id`, numbering
+    /// the release so a later one isn't expired along with it
+    released: Option<u64>,
+}
+
+#[derive(Default)]
+struct SharedRects {
+    rects: HashMap<String, SharedRect>,
+    releases: u64,
+}
+
+impl SharedRects {
+    fn record(&mut self, layout_id: String, rect: Rect<f64, f64>) {
+        self.rects.insert(
+            layout_id,
+            SharedRect {
+                rect,
+                released: None,
+            },
+        );
+    }
+
+    /// returns the release to expire once the update is over
+    fn release(&mut self, layout_id: &str) -> Option<u64> {
+        let shared = self.rects.get_mut(layout_id)?;
+        self.releases += 1;
+        shared.released = Some(self.releases);
+        shared.released
+    }
+
+    fn take(&mut self, layout_id: &str) -> Option<Rect<f64, f64>> {
+        match self.rects.get(layout_id) {
+            Some(shared) if shared.released.is_some() => {
+                self.rects.remove(layout_id).map(|shared| shared.rect)
+            }
+            _ => None,
+        }
+    }
+
+    /// forgets the rect if nobody took it over since `release`
+    fn expire(&mut self, layout_id: &str, release: u64) {
+        if self
+            .rects
+            .get(layout_id)
+            .is_some_and(|shared| shared.released == Some(release))
+        {
+            self.rects.remove(layout_id);
+        }
+    }
+}
+
+/// The last rect of every `Animatable` with a `layout_id`, for shared-element transitions.
+/// A newly mounted `Animatable` takes the rect its predecessor with the same `layout_id` left
+/// behind in the same update and animates from there.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LayoutRegistry {
+    /// not a signal: nothing renders from the rects, and they are recorded every frame
+    rects: CopyValue<SharedRects>,
+    /// where releases are expired from, since the releasing element's scope is going away
+    scope: ScopeId,
+}
+
+impl LayoutRegistry {
+    pub fn last_rect(&self, layout_id: &str) -> Option<Rect<f64, f64>> {
+        self.rects
+            .peek()
+            .rects
+            .get(layout_id)
+            .map(|shared| shared.rect)
+    }
+
+    /// the current rect of a mounted element
+    pub fn record(&mut self, layout_id: impl Into<String>, rect: Rect<f64, f64>) {
+        self.rects.write().record(layout_id.into(), rect);
+    }
+
+    /// the element recording under `layout_id` is going away, its rect can be taken over
+    /// until the next frame. the update mounting a successor is applied and its mounted
+    /// events run before that. does nothing once the registry itself is gone
+    pub fn release(&mut self, layout_id: &str) {
+        let Some(release) = self
+            .rects
+            .try_write()
+            .ok()
+            .and_then(|mut rects| rects.release(layout_id))
+        else {
+            return;
+        };
+        let mut rects = self.rects;
+        let layout_id = layout_id.to_string();
+        self.scope.spawn(async move {
+            FrameDriver::new(None).next_frame().await;
+            if let Ok(mut rects) = rects.try_write() {
+                rects.expire(&layout_id, release);
+            }
+        });
+    }
+
+    /// removes the rect, so it's only animated from once. rects of elements still mounted,
+    /// or released before the last frame, aren't handed out
+    pub fn take(&mut self, layout_id: &str) -> Option<Rect<f64, f64>> {
+        self.rects.write().take(layout_id)
+    }
+}
+
+/// Provides a [`LayoutRegistry`] to every `Animatable` below. Call it in a component above
+/// all the views sharing elements, so it outlives both ends of a transition.
+pub fn use_layout_registry() -> LayoutRegistry {
+    use_context_provider(|| LayoutRegistry {
+        rects: CopyValue::new(SharedRects::default()),
+        scope: current_scope_id().expect("in a component"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use dioxus::html::geometry::euclid::{Point2D, Size2D};
+
+    use super::*;
+
+    fn rect(x: f64) -> Rect<f64, f64> {
+        Rect::new(Point2D::new(x, 0.), Size2D::new(10., 10.))
+    }
+
+    #[test]
+    fn only_released_rects_are_taken_once() {
+        let mut rects = SharedRects::default();
+        rects.record("card".into(), rect(1.));
+        // still mounted
+        assert_eq!(rects.take("card"), None);
+
+        rects.release("card");
+        assert_eq!(rects.take("card"), Some(rect(1.)));
+        assert_eq!(rects.take("card"), None);
+    }
+
+    #[test]
+    fn rects_nobody_took_expire() {
+        let mut rects = SharedRects::default();
+        rects.record("card".into(), rect(1.));
+        let release = rects.release("card").unwrap();
+        rects.expire("card", release);
+        assert_eq!(rects.take("card"), None);
+        assert!(rects.rects.is_empty());
+    }
+
+    #[test]
+    fn only_the_same_release_expires() {
+        let mut rects = SharedRects::default();
+        rects.record("card".into(), rect(1.));
+        let first = rects.release("card").unwrap();
+        // mounted again and released in a later update, before the first expiry ran
+        rects.record("card".into(), rect(2.));
+        rects.release("card");
+        rects.expire("card", first);
+        assert_eq!(rects.take("card"), Some(rect(2.)));
+    }
+
+    #[test]
+    fn recording_again_revives_the_rect() {
+        let mut rects = SharedRects::default();
+        rects.record("card".into(), rect(1.));
+        let release = rects.release("card").unwrap();
+        rects.record("card".into(), rect(2.));
+        assert_eq!(rects.take("card"), None);
+        rects.expire("card", release);
+        assert_eq!(
+            rects.rects.get("card").map(|shared| shared.rect),
+            Some(rect(2.))
+        );
+    }
+}
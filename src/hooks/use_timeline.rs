@@ -0,0 +1,344 @@
+use dioxus::{
+    dioxus_core::Task,
+    hooks::{use_effect, use_signal},
+    html::geometry::euclid::Rect,
+    prelude::spawn,
+    signals::{Readable, Signal, Writable},
+};
+use web_time::Duration;
+
+use crate::{
+    clock::{MonotonicClock, SharedClock},
+    controllers::{Channels, FlipbookStatus, TimelineBuilder, Track},
+    frame_driver::FrameDriver,
+    stopwatch::Stopwatch,
+};
+
+use super::UseFlipbook;
+
+struct TimelinePlayback {
+    tracks: Vec<Track<Signal<UseFlipbook>>>,
+    /// the frame each track last sent its controller
+    last_frames: Vec<Option<(Rect<f64, f64>, Channels)>>,
+    duration: Duration,
+    stopwatch: Stopwatch,
+    /// timeline time and stopwatch reading at the last seek
+    anchor: (Duration, Duration),
+    elapsed: Duration,
+    paused: bool,
+}
+
+impl TimelinePlayback {
+    /// takes the controllers over from their own queues, starting from where they are now
+    fn new(timeline: &TimelineBuilder<Signal<UseFlipbook>>, clock: SharedClock) -> Self {
+        let resolved = timeline.resolve(|controller| {
+            let flipbook = controller.peek().clone();
+            (flipbook.peek_rect(), flipbook.peek_channels())
+        });
+        for track in &resolved.tracks {
+            track.target.peek().clone().drop_all();
+        }
+
+        let mut stopwatch = Stopwatch::new(clock);
+        stopwatch.start();
+        Self {
+            last_frames: vec![None; resolved.tracks.len()],
+            tracks: resolved.tracks,
+            duration: resolved.duration,
+            stopwatch,
+            anchor: (Duration::ZERO, Duration::ZERO),
+            elapsed: Duration::ZERO,
+            paused: false,
+        }
+    }
+
+    fn current_time(&mut self) -> Duration {
+        if self.paused {
+            return self.elapsed;
+        }
+        let (anchor_elapsed, anchor_stopwatch) = self.anchor;
+        let since = self
+            .stopwatch
+            .get_elapsed()
+            .saturating_sub(anchor_stopwatch);
+        anchor_elapsed.saturating_add(since).min(self.duration)
+    }
+
+    fn seek(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed.min(self.duration);
+        self.anchor = (self.elapsed, self.stopwatch.get_elapsed());
+    }
+
+    fn seek_progress(&mut self, progress: f64) {
+        if progress.is_nan() {
+            tracing::error!("ignoring timeline seek to progress {}", progress);
+            return;
+        }
+        // a timeline repeating forever has no end to measure from
+        if self.duration != Duration::MAX {
+            let elapsed = self.duration.as_secs_f64() * progress.clamp(0., 1.);
+            self.seek(Duration::try_from_secs_f64(elapsed).unwrap_or(self.duration));
+        }
+    }
+
+    fn pause(&mut self) {
+        self.elapsed = self.current_time();
+        self.paused = true;
+        self.stopwatch.stop();
+    }
+
+    fn resume(&mut self) {
+        if self.paused && self.elapsed < self.duration {
+            self.paused = false;
+            self.stopwatch.start();
+            self.seek(self.elapsed);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    fn progress(&self) -> f64 {
+        if self.duration.is_zero() {
+            return 1.;
+        }
+        (self.elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.)
+    }
+
+    /// sends every controller its frame at the current time, if it changed
+    fn render(&mut self) {
+        self.elapsed = self.current_time();
+        if self.is_finished() {
+            self.pause();
+        }
+        for (track, last_frame) in self.tracks.iter().zip(&mut self.last_frames) {
+            let (Some(rect), channels) = track.frame_at(self.elapsed) else {
+                continue;
+            };
+            let frame = Some((rect, channels));
+            if *last_frame != frame {
+                if let Some((rect, channels)) = frame.clone() {
+                    track.target.peek().clone().set_frame(rect, channels);
+                }
+                *last_frame = frame;
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum TimelineCommand {
+    Play(TimelineBuilder<Signal<UseFlipbook>>),
+    Pause,
+    Resume,
+    Seek(f64),
+    SeekTime(Duration),
+    Cancel,
+}
+
+/// Plays a [`TimelineBuilder`] across its controllers as one unit
+#[derive(Clone, PartialEq)]
+pub struct UseTimeline {
+    status: Signal<FlipbookStatus>,
+    progress: Signal<Option<f64>>,
+    commands: Signal<Vec<TimelineCommand>>,
+}
+
+impl UseTimeline {
+    /// replaces the timeline playing, if any. the controllers involved drop their own
+    /// queues and start from where they are
+    pub fn play(&mut self, timeline: TimelineBuilder<Signal<UseFlipbook>>) {
+        self.send(TimelineCommand::Play(timeline));
+    }
+
+    pub fn pause(&mut self) {
+        self.send(TimelineCommand::Pause);
+    }
+
+    pub fn resume(&mut self) {
+        self.send(TimelineCommand::Resume);
+    }
+
+    /// jumps every controller to where the timeline is at `progress`, `0.0` to `1.0`.
+    /// works while paused and after the timeline finished
+    pub fn seek(&mut self, progress: f64) {
+        self.send(TimelineCommand::Seek(progress));
+    }
+
+    pub fn seek_time(&mut self, elapsed: Duration) {
+        self.send(TimelineCommand::SeekTime(elapsed));
+    }
+
+    /// stops the timeline, leaving every controller where it is
+    pub fn cancel(&mut self) {
+        self.send(TimelineCommand::Cancel);
+    }
+
+    /// busy until the timeline reaches its end, paused or not
+    pub fn peek_status(&self) -> FlipbookStatus {
+        self.status.peek().clone()
+    }
+
+    pub fn read_status(&self) -> FlipbookStatus {
+        self.status.read().clone()
+    }
+
+    /// `None` when nothing was played or the timeline was cancelled
+    pub fn peek_progress(&self) -> Option<f64> {
+        *self.progress.peek()
+    }
+
+    pub fn read_progress(&self) -> Option<f64> {
+        *self.progress.read()
+    }
+
+    fn send(&mut self, command: TimelineCommand) {
+        self.commands.write().push(command);
+    }
+}
+
+fn use_timeline(clock: SharedClock) -> UseTimeline {
+    let mut status = use_signal(|| FlipbookStatus::Resting);
+    let mut progress = use_signal(|| None as Option<f64>);
+    let mut commands = use_signal(Vec::<TimelineCommand>::new);
+
+    let mut playback = use_signal(|| None as Option<TimelinePlayback>);
+    let mut frame_loop: Signal<Option<Task>> = use_signal(|| None);
+
+    let mut render = move || {
+        let (timeline_status, timeline_progress) = match playback.write().as_mut() {
+            Some(playback) => {
+                playback.render();
+                let status = if playback.is_finished() {
+                    FlipbookStatus::Resting
+                } else {
+                    FlipbookStatus::Busy
+                };
+                (status, Some(playback.progress()))
+            }
+            None => (FlipbookStatus::Resting, None),
+        };
+        if *status.peek() != timeline_status {
+            status.set(timeline_status);
+        }
+        if *progress.peek() != timeline_progress {
+            progress.set(timeline_progress);
+        }
+    };
+
+    let mut ensure_running = move || {
+        let needs_frames = playback
+            .peek()
+            .as_ref()
+            .is_some_and(|playback| !playback.paused);
+        if frame_loop.peek().is_some() || !needs_frames {
+            return;
+        }
+        let handle = spawn(async move {
+            let mut frames = FrameDriver::new(None);
+            loop {
+                render();
+                let needs_frames = playback
+                    .peek()
+                    .as_ref()
+                    .is_some_and(|playback| !playback.paused);
+                if !needs_frames {
+                    break;
+                }
+                frames.next_frame().await;
+            }
+            frame_loop.set(None);
+        });
+        frame_loop.set(Some(handle));
+    };
+
+    use_effect(move || {
+        if commands.read().is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut *commands.write());
+        for cmd in pending {
+            let mut playback = playback.write();
+            match cmd {
+                TimelineCommand::Play(timeline) => {
+                    *playback = Some(TimelinePlayback::new(&timeline, clock.clone()));
+                }
+                TimelineCommand::Cancel => *playback = None,
+                cmd => {
+                    let Some(playback) = playback.as_mut() else {
+                        continue;
+                    };
+                    match cmd {
+                        TimelineCommand::Pause => playback.pause(),
+                        TimelineCommand::Resume => playback.resume(),
+                        TimelineCommand::Seek(progress) => playback.seek_progress(progress),
+                        TimelineCommand::SeekTime(elapsed) => playback.seek(elapsed),
+                        TimelineCommand::Play(_) | TimelineCommand::Cancel => {}
+                    }
+                }
+            }
+        }
+        render();
+        ensure_running();
+    });
+
+    UseTimeline {
+        status,
+        progress,
+        commands,
+    }
+}
+
+pub fn use_timeline_signal() -> Signal<UseTimeline> {
+    use_timeline_signal_with_clock(MonotonicClock::shared())
+}
+
+/// `clock` is only read on the first render
+pub fn use_timeline_signal_with_clock(clock: SharedClock) -> Signal<UseTimeline> {
+    let timeline = use_timeline(clock);
+    use_signal(|| timeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::VirtualClock;
+
+    use super::*;
+
+    fn playback(duration: Duration) -> TimelinePlayback {
+        let mut stopwatch = Stopwatch::new(VirtualClock::new().shared());
+        stopwatch.start();
+        TimelinePlayback {
+            tracks: Vec::new(),
+            last_frames: Vec::new(),
+            duration,
+            stopwatch,
+            anchor: (Duration::ZERO, Duration::ZERO),
+            elapsed: Duration::ZERO,
+            paused: true,
+        }
+    }
+
+    #[test]
+    fn seeks_clamp_to_the_timeline() {
+        let mut playback = playback(Duration::from_millis(1000));
+        playback.seek_progress(0.25);
+        assert_eq!(playback.elapsed, Duration::from_millis(250));
+        playback.seek_progress(f64::NAN);
+        assert_eq!(playback.elapsed, Duration::from_millis(250));
+        playback.seek_progress(f64::INFINITY);
+        assert!(playback.is_finished());
+        playback.seek(Duration::from_secs(5));
+        assert_eq!(playback.elapsed, Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn endless_timelines_only_seek_by_time() {
+        let mut playback = playback(Duration::MAX);
+        playback.seek_progress(0.5);
+        assert_eq!(playback.elapsed, Duration::ZERO);
+        playback.seek(Duration::from_secs(3));
+        assert_eq!(playback.elapsed, Duration::from_secs(3));
+    }
+}
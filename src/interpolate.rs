@@ -0,0 +1,130 @@
+use std::fmt::Debug;
+
+use euclid::{Point2D, Rect, Size2D, Vector2D};
+
+use crate::color::{Color, ColorSpace};
+
+/// A value that transitions can animate.
+///
+/// Springs carrying the velocity of an interrupted motion extrapolate far outside `0..=1`,
+/// see [`Momentum`](crate::controllers::Momentum). That only works for types interpolating
+/// linearly, like numbers, points and rects. [`Color`] clips to its gamut and alpha range.
+pub trait Interpolate: Clone + PartialEq + Debug {
+    /// `t` is eased progress from `self` (0) to `to` (1). easings like `BackOut` overshoot,
+    /// so types should extrapolate outside that range where it makes sense.
+    fn interpolate(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t as f32
+    }
+}
+
+impl<T: Interpolate, U> Interpolate for Point2D<T, U> {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        Point2D::new(self.x.interpolate(&to.x, t), self.y.interpolate(&to.y, t))
+    }
+}
+
+impl<T: Interpolate, U> Interpolate for Vector2D<T, U> {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        Vector2D::new(self.x.interpolate(&to.x, t), self.y.interpolate(&to.y, t))
+    }
+}
+
+impl<T: Interpolate, U> Interpolate for Size2D<T, U> {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        Size2D::new(
+            self.width.interpolate(&to.width, t),
+            self.height.interpolate(&to.height, t),
+        )
+    }
+}
+
+impl<T: Interpolate, U> Interpolate for Rect<T, U> {
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        Rect::new(
+            self.origin.interpolate(&to.origin, t),
+            self.size.interpolate(&to.size, t),
+        )
+    }
+}
+
+impl Interpolate for Color {
+    /// blends in [`ColorSpace::Oklab`], see [`Color::interpolate_in`] for other spaces
+    fn interpolate(&self, to: &Self, t: f64) -> Self {
+        self.interpolate_in(to, t, ColorSpace::default())
+    }
+}
+
+macro_rules! impl_interpolate_for_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Interpolate),+> Interpolate for ($($name,)+) {
+            fn interpolate(&self, to: &Self, t: f64) -> Self {
+                ($(self.$index.interpolate(&to.$index, t),)+)
+            }
+        }
+    };
+}
+
+impl_interpolate_for_tuple!(A 0, B 1);
+impl_interpolate_for_tuple!(A 0, B 1, C 2);
+impl_interpolate_for_tuple!(A 0, B 1, C 2, D 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect<f64, f64> {
+        Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+    }
+
+    #[test]
+    fn numbers_extrapolate() {
+        assert_eq!(10_f64.interpolate(&20., 0.5), 15.);
+        assert_eq!(10_f64.interpolate(&20., 2.), 30.);
+        assert_eq!(10_f64.interpolate(&20., -1.), 0.);
+        assert_eq!(10_f32.interpolate(&20., 1001.), 10020.);
+    }
+
+    #[test]
+    fn geometry_interpolates_per_component() {
+        let from = rect(0., 10., 100., 50.);
+        let to = rect(10., 0., 200., 50.);
+        assert_eq!(from.interpolate(&to, 0.5), rect(5., 5., 150., 50.));
+        // as far out as momentum samples
+        assert_eq!(
+            from.interpolate(&to, 1001.),
+            rect(10010., -10000., 100200., 50.)
+        );
+        assert_eq!(
+            Vector2D::<f64, f64>::new(1., 2.).interpolate(&Vector2D::new(3., 2.), 2.),
+            Vector2D::new(5., 2.)
+        );
+    }
+
+    #[test]
+    fn tuples_interpolate_per_element() {
+        let from = (0_f64, Point2D::<f64, f64>::new(0., 0.), 1_f32);
+        let to = (4_f64, Point2D::new(2., -2.), 3_f32);
+        assert_eq!(from.interpolate(&to, 2.), (8., Point2D::new(4., -4.), 5.));
+    }
+
+    #[test]
+    fn colors_clip_instead_of_extrapolating() {
+        let from = Color::new(0.2, 0.2, 0.2, 0.5);
+        let to = Color::new(0.4, 0.4, 0.4, 1.);
+        let beyond = from.interpolate(&to, 2.);
+        assert_eq!(beyond.a, 1.);
+        assert!(beyond.r <= 1.);
+        let far = from.interpolate(&to, 1001.);
+        assert_eq!((far.r, far.a), (1., 1.));
+    }
+}
@@ -1,5 +1,13 @@
+pub mod clock;
+pub mod color;
+#[cfg(feature = "dioxus")]
 pub mod components;
 pub mod controllers;
+pub mod css;
 pub mod easing;
+#[cfg(feature = "dioxus")]
+pub mod frame_driver;
+#[cfg(feature = "dioxus")]
 pub mod hooks;
+pub mod interpolate;
 pub mod stopwatch;
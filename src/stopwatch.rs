@@ -1,14 +1,44 @@
+use std::{fmt, rc::Rc};
+
+#[cfg(feature = "dioxus")]
 use dioxus::{hooks::use_signal, signals::Signal};
 
-#[derive(Clone, PartialEq, Debug)]
+use crate::clock::{MonotonicClock, SharedClock};
+
+#[derive(Clone)]
 pub struct Stopwatch {
-    lap_start: Option<web_time::SystemTime>,
+    clock: SharedClock,
+    lap_start: Option<web_time::Duration>,
     elapsed: web_time::Duration,
 }
 
+impl Default for Stopwatch {
+    fn default() -> Self {
+        Self::new(MonotonicClock::shared())
+    }
+}
+
+impl fmt::Debug for Stopwatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stopwatch")
+            .field("lap_start", &self.lap_start)
+            .field("elapsed", &self.elapsed)
+            .finish()
+    }
+}
+
+impl PartialEq for Stopwatch {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.clock, &other.clock)
+            && self.lap_start == other.lap_start
+            && self.elapsed == other.elapsed
+    }
+}
+
 impl Stopwatch {
-    fn new() -> Self {
+    pub fn new(clock: SharedClock) -> Self {
         Self {
+            clock,
             lap_start: None,
             elapsed: web_time::Duration::ZERO,
         }
@@ -20,14 +50,14 @@ impl Stopwatch {
     }
 
     pub fn start(&mut self) {
-        self.lap_start = Some(web_time::SystemTime::now());
+        self.lap_start = Some(self.clock.now());
     }
 
     fn lap(&mut self) {
         if let Some(lap_start) = self.lap_start {
-            let lap_elapsed = lap_start.elapsed().unwrap();
-            self.elapsed = self.elapsed.checked_add(lap_elapsed).unwrap();
-            self.start();
+            let now = self.clock.now();
+            self.elapsed = self.elapsed.saturating_add(now.saturating_sub(lap_start));
+            self.lap_start = Some(now);
         }
     }
 
@@ -42,6 +72,42 @@ impl Stopwatch {
     }
 }
 
+#[cfg(feature = "dioxus")]
 pub fn use_stopwatch_signal() -> Signal<Stopwatch> {
-    use_signal(|| Stopwatch::new())
+    use_stopwatch_signal_with_clock(MonotonicClock::shared())
+}
+
+#[cfg(feature = "dioxus")]
+pub fn use_stopwatch_signal_with_clock(clock: SharedClock) -> Signal<Stopwatch> {
+    use_signal(|| Stopwatch::new(clock))
+}
+
+#[cfg(test)]
+mod tests {
+    use web_time::Duration;
+
+    use crate::clock::VirtualClock;
+
+    use super::*;
+
+    #[test]
+    fn counts_only_while_running() {
+        let clock = VirtualClock::new();
+        let mut stopwatch = Stopwatch::new(clock.shared());
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(stopwatch.get_elapsed(), Duration::ZERO);
+
+        stopwatch.start();
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(stopwatch.get_elapsed(), Duration::from_millis(250));
+        stopwatch.stop();
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(stopwatch.get_elapsed(), Duration::from_millis(250));
+
+        stopwatch.start();
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(stopwatch.get_elapsed(), Duration::from_millis(300));
+        stopwatch.clear();
+        assert_eq!(stopwatch.get_elapsed(), Duration::ZERO);
+    }
 }